@@ -0,0 +1,210 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::models::Location;
+
+// Sun altitude at sunrise/sunset, allowing for refraction and the solar disc
+const SUNRISE_ALTITUDE: f64 = -0.833;
+
+const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2440587.5;
+
+fn days_since_j2000(time: DateTime<Utc>) -> f64 {
+    time.timestamp() as f64 / 86400.0 + UNIX_EPOCH_JULIAN_DAY - J2000
+}
+
+/// Sun's altitude above the horizon in degrees, using the low-precision
+/// solar coordinates from the Astronomical Almanac (accurate to ~0.01°).
+pub fn sun_altitude(lat: f64, lon: f64, time: DateTime<Utc>) -> f64 {
+    let n = days_since_j2000(time);
+    let mean_longitude = (280.460 + 0.9856474 * n).rem_euclid(360.0);
+    let mean_anomaly = (357.528 + 0.9856003 * n).rem_euclid(360.0).to_radians();
+    let ecliptic_longitude = (mean_longitude
+        + 1.915 * mean_anomaly.sin()
+        + 0.020 * (2.0 * mean_anomaly).sin())
+    .to_radians();
+    let obliquity = (23.439 - 0.0000004 * n).to_radians();
+
+    let right_ascension = (obliquity.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
+    let declination = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+
+    let sidereal_degrees = (280.46061837 + 360.98564736629 * n + lon).rem_euclid(360.0);
+    let hour_angle = sidereal_degrees.to_radians() - right_ascension;
+
+    let lat = lat.to_radians();
+    (lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos())
+        .asin()
+        .to_degrees()
+}
+
+/// Whether the sun is up at `location`, i.e. `time` falls between sunrise and sunset.
+pub fn is_daylight(location: &Location, time: DateTime<Utc>) -> bool {
+    sun_altitude(location.lat, location.lon, time) > SUNRISE_ALTITUDE
+}
+
+// Sun altitudes for the twilight boundaries
+const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+const NAUTICAL_TWILIGHT_ALTITUDE: f64 = -12.0;
+const ASTRONOMICAL_TWILIGHT_ALTITUDE: f64 = -18.0;
+
+const SYNODIC_MONTH_DAYS: f64 = 29.530588853;
+// Julian day of the new moon of 2000-01-06 18:14 UTC
+const REFERENCE_NEW_MOON: f64 = 2451550.1;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MoonPhase {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoonPhase::NewMoon => "New Moon",
+            MoonPhase::WaxingCrescent => "Waxing Crescent",
+            MoonPhase::FirstQuarter => "First Quarter",
+            MoonPhase::WaxingGibbous => "Waxing Gibbous",
+            MoonPhase::FullMoon => "Full Moon",
+            MoonPhase::WaningGibbous => "Waning Gibbous",
+            MoonPhase::LastQuarter => "Last Quarter",
+            MoonPhase::WaningCrescent => "Waning Crescent",
+        }
+    }
+}
+
+/// Times the sun crosses a given altitude on one day. Both are `None` when it
+/// stays above or below that altitude all day.
+#[derive(Serialize, Clone, Copy, Debug)]
+pub struct SunCrossing {
+    pub rise: Option<DateTime<Utc>>,
+    pub set: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AstroData {
+    pub date: NaiveDate,
+    pub solar_noon: DateTime<Utc>,
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub civil_twilight: SunCrossing,
+    pub nautical_twilight: SunCrossing,
+    pub astronomical_twilight: SunCrossing,
+    pub day_length_seconds: i64,
+    pub moon_phase: MoonPhase,
+    /// Illuminated fraction of the moon's disc, 0.0 to 1.0
+    pub moon_illumination: f64,
+    pub moon_age_days: f64,
+}
+
+impl AstroData {
+    pub fn day_length(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.day_length_seconds)
+    }
+}
+
+/// Sun and moon data for `location` on `date`, using the sunrise equation and
+/// a mean synodic month for the moon (good to a few minutes / ~half a day).
+pub fn compute(location: &Location, date: NaiveDate) -> AstroData {
+    let transit = solar_transit(location.lon, date);
+    let declination = (transit.ecliptic_longitude.sin() * 23.4397_f64.to_radians().sin()).asin();
+    let crossing = |altitude: f64| sun_crossing(location.lat, declination, transit.julian_day, altitude);
+
+    let sun = crossing(SUNRISE_ALTITUDE);
+    let day_length_seconds = match (sun.rise, sun.set) {
+        (Some(rise), Some(set)) => (set - rise).num_seconds(),
+        // No crossing: either polar day or polar night
+        _ if sun_altitude(location.lat, location.lon, from_julian_day(transit.julian_day)) > SUNRISE_ALTITUDE => 86400,
+        _ => 0,
+    };
+
+    let moon_age_days = (transit.julian_day - REFERENCE_NEW_MOON).rem_euclid(SYNODIC_MONTH_DAYS);
+    let moon_fraction = moon_age_days / SYNODIC_MONTH_DAYS;
+
+    AstroData {
+        date,
+        solar_noon: from_julian_day(transit.julian_day),
+        sunrise: sun.rise,
+        sunset: sun.set,
+        civil_twilight: crossing(CIVIL_TWILIGHT_ALTITUDE),
+        nautical_twilight: crossing(NAUTICAL_TWILIGHT_ALTITUDE),
+        astronomical_twilight: crossing(ASTRONOMICAL_TWILIGHT_ALTITUDE),
+        day_length_seconds,
+        moon_phase: moon_phase(moon_age_days),
+        moon_illumination: (1.0 - (2.0 * std::f64::consts::PI * moon_fraction).cos()) / 2.0,
+        moon_age_days,
+    }
+}
+
+struct SolarTransit {
+    julian_day: f64,
+    ecliptic_longitude: f64,
+}
+
+fn solar_transit(lon: f64, date: NaiveDate) -> SolarTransit {
+    let j2000_date = NaiveDate::from_ymd_opt(2000, 1, 1).expect("valid date");
+    let day = (date - j2000_date).num_days() as f64 - lon / 360.0;
+
+    let mean_anomaly = (357.5291 + 0.98560028 * day).rem_euclid(360.0).to_radians();
+    let center = 1.9148 * mean_anomaly.sin()
+        + 0.0200 * (2.0 * mean_anomaly).sin()
+        + 0.0003 * (3.0 * mean_anomaly).sin();
+    let ecliptic_longitude = (mean_anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+
+    SolarTransit {
+        julian_day: J2000 + day + 0.0053 * mean_anomaly.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin(),
+        ecliptic_longitude,
+    }
+}
+
+fn sun_crossing(lat: f64, declination: f64, transit: f64, altitude: f64) -> SunCrossing {
+    let lat = lat.to_radians();
+    let cos_hour_angle = (altitude.to_radians().sin() - lat.sin() * declination.sin())
+        / (lat.cos() * declination.cos());
+
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return SunCrossing { rise: None, set: None };
+    }
+
+    let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+    SunCrossing {
+        rise: Some(from_julian_day(transit - half_day)),
+        set: Some(from_julian_day(transit + half_day)),
+    }
+}
+
+fn from_julian_day(julian_day: f64) -> DateTime<Utc> {
+    let seconds = ((julian_day - UNIX_EPOCH_JULIAN_DAY) * 86400.0).round() as i64;
+    DateTime::from_timestamp(seconds, 0).unwrap_or_default()
+}
+
+/// Names the principal phases within a day of their exact time, and the
+/// intermediate phases in between.
+fn moon_phase(age_days: f64) -> MoonPhase {
+    let quarter = SYNODIC_MONTH_DAYS / 4.0;
+    let nearest = (age_days / quarter).round();
+
+    if (age_days - nearest * quarter).abs() < 1.0 {
+        match nearest as u8 {
+            1 => MoonPhase::FirstQuarter,
+            2 => MoonPhase::FullMoon,
+            3 => MoonPhase::LastQuarter,
+            _ => MoonPhase::NewMoon,
+        }
+    } else {
+        match (age_days / quarter) as u8 {
+            0 => MoonPhase::WaxingCrescent,
+            1 => MoonPhase::WaxingGibbous,
+            2 => MoonPhase::WaningGibbous,
+            _ => MoonPhase::WaningCrescent,
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::WeatherError;
+use crate::models::{Location, WeatherData};
+
+const CACHE_DIR_NAME: &str = "nws-weather";
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    stored_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    #[serde(default)]
+    validators: Validators,
+    body: String,
+}
+
+/// HTTP validators used to revalidate an expired entry with a conditional request.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LastKnown {
+    key: String,
+    location: Location,
+    weather: WeatherData,
+}
+
+pub struct CachedResponse {
+    pub body: String,
+    pub validators: Validators,
+    pub fresh: bool,
+}
+
+/// On-disk response cache keyed by request URL.
+///
+/// Cache failures are never fatal: unreadable or corrupt entries are treated
+/// as misses and write errors are ignored, so a broken cache directory only
+/// costs extra requests.
+pub struct Cache {
+    dir: Option<PathBuf>,
+    /// Whether API responses are cached; the last known weather always is
+    responses: bool,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir: Some(dir), responses: true }
+    }
+
+    pub fn disabled() -> Self {
+        Self { dir: None, responses: false }
+    }
+
+    /// Cache rooted at `$XDG_CACHE_HOME/nws-weather`, falling back to
+    /// `$HOME/.cache/nws-weather`. Disabled if neither variable is set.
+    pub fn from_env() -> Self {
+        Self { dir: Self::default_dir(), responses: true }
+    }
+
+    /// Stops caching API responses but keeps the last known weather, so
+    /// bypassing the cache doesn't also turn off the stale fallback.
+    pub fn without_responses(mut self) -> Self {
+        self.responses = false;
+        self
+    }
+
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(base.join(CACHE_DIR_NAME))
+    }
+
+    pub fn dir(&self) -> Option<&Path> {
+        self.dir.as_deref()
+    }
+
+    fn entry_path(&self, url: &str) -> Option<PathBuf> {
+        self.dir.as_ref().filter(|_| self.responses).map(|dir| dir.join(format!("{:016x}.json", fnv1a(url))))
+    }
+
+    /// Returns the cached response for `url`, expired or not.
+    pub fn lookup(&self, url: &str) -> Option<CachedResponse> {
+        let entry = self.read_entry(url)?;
+        Some(CachedResponse {
+            fresh: entry.expires_at > Utc::now(),
+            body: entry.body,
+            validators: entry.validators,
+        })
+    }
+
+    fn read_entry(&self, url: &str) -> Option<CacheEntry> {
+        let contents = fs::read_to_string(self.entry_path(url)?).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+        // Guard against hash collisions
+        (entry.url == url).then_some(entry)
+    }
+
+    pub fn put(&self, url: &str, body: &str, validators: &Validators, expires_at: DateTime<Utc>) {
+        let Some(path) = self.entry_path(url) else {
+            return;
+        };
+
+        let entry = CacheEntry {
+            url: url.to_string(),
+            stored_at: Utc::now(),
+            expires_at,
+            validators: validators.clone(),
+            body: body.to_string(),
+        };
+
+        if let Ok(contents) = serde_json::to_string(&entry) {
+            let _ = write_atomic(&path, &contents);
+        }
+    }
+
+    fn last_known_path(&self, key: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("last-known-{:016x}.json", fnv1a(key))))
+    }
+
+    /// Remembers the last successfully fetched weather for `key`, used as a
+    /// fallback when a later refresh fails.
+    pub fn store_last_known(&self, key: &str, location: &Location, weather: &WeatherData) {
+        let Some(path) = self.last_known_path(key) else {
+            return;
+        };
+
+        let last_known = LastKnown {
+            key: key.to_string(),
+            location: location.clone(),
+            weather: weather.clone(),
+        };
+
+        if let Ok(contents) = serde_json::to_string(&last_known) {
+            let _ = write_atomic(&path, &contents);
+        }
+    }
+
+    pub fn load_last_known(&self, key: &str) -> Option<(Location, WeatherData)> {
+        let contents = fs::read_to_string(self.last_known_path(key)?).ok()?;
+        let last_known: LastKnown = serde_json::from_str(&contents).ok()?;
+        (last_known.key == key).then_some((last_known.location, last_known.weather))
+    }
+
+    /// Removes every cached entry, returning how many files were deleted.
+    pub fn clear(&self) -> Result<usize, WeatherError> {
+        let Some(dir) = &self.dir else {
+            return Ok(0);
+        };
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+/// Writes via a temporary file and rename so concurrent invocations (one per
+/// monitor) never observe a half-written entry.
+fn write_atomic(path: &Path, contents: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension(format!("tmp.{}", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+/// Stable 64-bit FNV-1a hash used for cache file names.
+fn fnv1a(input: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    input.bytes().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
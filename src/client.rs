@@ -1,10 +1,17 @@
-use reqwest::Client;
+use chrono::{DateTime, DurationRound, Utc};
+use reqwest::header::{HeaderValue, ETAG, EXPIRES, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 use std::time::Duration;
 use tokio::time::sleep;
 
+use crate::cache::{Cache, Validators};
+use crate::comfort;
 use crate::error::WeatherError;
+use crate::gridpoint::{GridpointData, GridpointResponse};
+use crate::history::{History, Reading};
 use crate::models::*;
+use crate::units::Unit;
 
 // Constants
 const USER_AGENT: &str = "waybar-weather-cli/2.0 (github.com/user/weather-cli)";
@@ -12,12 +19,57 @@ const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
 const MAX_RETRIES: u32 = 5;
 const RETRY_DELAY_MS: u64 = 2000;
 
-// Temperature conversion
-const CELSIUS_TO_FAHRENHEIT_MULTIPLIER: f64 = 9.0 / 5.0;
-const CELSIUS_TO_FAHRENHEIT_OFFSET: f64 = 32.0;
+// Cache lifetimes per endpoint
+const ZIP_CACHE_TTL: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+const POINT_CACHE_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const STATIONS_CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const FORECAST_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+const OBSERVATION_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+const ALERTS_CACHE_TTL: Duration = Duration::from_secs(2 * 60);
+const MIN_EXPIRES_TTL: Duration = Duration::from_secs(60);
+
+// Observation station selection
+const MAX_STATION_ATTEMPTS: usize = 3;
+const MAX_OBSERVATION_AGE: Duration = Duration::from_secs(2 * 60 * 60);
+
+// Accumulation summary windows, in hours
+const ACCUMULATION_WINDOWS: [u32; 3] = [24, 48, 72];
+
+#[derive(Clone, Copy)]
+enum CacheTtl {
+    /// Keep the response for a fixed time
+    Fixed(Duration),
+    /// Honor the response's `Expires` header, or use the fallback if absent
+    Expires(Duration),
+}
+
+impl CacheTtl {
+    fn expires_at(self, expires_header: Option<DateTime<Utc>>) -> DateTime<Utc> {
+        let now = Utc::now();
+        let after = |ttl: Duration| now + chrono::Duration::from_std(ttl).unwrap_or_default();
+        match (self, expires_header) {
+            (CacheTtl::Expires(_), Some(expires)) => expires.max(after(MIN_EXPIRES_TTL)),
+            (CacheTtl::Expires(ttl), None) | (CacheTtl::Fixed(ttl), _) => after(ttl),
+        }
+    }
+}
+
+enum Fetched {
+    Modified { body: String, validators: Validators },
+    NotModified,
+}
 
 pub struct WeatherClient {
     client: Client,
+    cache: Cache,
+    station: Option<String>,
+    max_observation_age: Duration,
+}
+
+impl Default for WeatherClient {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl WeatherClient {
@@ -29,7 +81,34 @@ impl WeatherClient {
             .build()
             .expect("Failed to create HTTP client");
         
-        Self { client }
+        Self {
+            client,
+            cache: Cache::disabled(),
+            station: None,
+            max_observation_age: MAX_OBSERVATION_AGE,
+        }
+    }
+
+    pub fn with_cache(mut self, cache: Cache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Always observe from this station (e.g. "KSFO") instead of the nearest
+    /// one reporting.
+    pub fn with_station(mut self, station: impl Into<String>) -> Self {
+        self.station = Some(station.into().to_uppercase());
+        self
+    }
+
+    /// Observations older than this are skipped in favor of the next station.
+    pub fn with_max_observation_age(mut self, age: Duration) -> Self {
+        self.max_observation_age = age;
+        self
+    }
+
+    pub fn cache(&self) -> &Cache {
+        &self.cache
     }
 
     pub async fn wait_for_network(&self) -> Result<(), WeatherError> {
@@ -59,29 +138,101 @@ impl WeatherClient {
          Err(WeatherError::Api("Network connectivity check failed".to_string()))
     }
 
-    async fn get_with_retry<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T, WeatherError> {
+    async fn get_with_retry<T: for<'de> Deserialize<'de>>(&self, url: &str, ttl: CacheTtl) -> Result<T, WeatherError> {
+        self.get_with_attempts(url, ttl, MAX_RETRIES).await
+    }
+
+    async fn get_with_attempts<T: for<'de> Deserialize<'de>>(&self, url: &str, ttl: CacheTtl, attempts: u32) -> Result<T, WeatherError> {
+        // A cached body that no longer parses is treated as a miss: it is
+        // neither served nor revalidated, so a 304 can't hand it back
+        let cached = self.cache.lookup(url)
+            .and_then(|cached| serde_json::from_str::<T>(&cached.body).ok().map(|data| (cached, data)));
+
+        let cached = match cached {
+            Some((cached, data)) if cached.fresh => return Ok(data),
+            cached => cached,
+        };
+
+        let validators = cached.as_ref()
+            .map(|(cached, _)| &cached.validators)
+            .filter(|validators| !validators.is_empty());
+
+        let (fetched, expires) = self.fetch_with_retry(url, validators, attempts).await?;
+        match (fetched, cached) {
+            (Fetched::Modified { body, validators }, _) => {
+                let data = serde_json::from_str(&body)?;
+                self.cache.put(url, &body, &validators, ttl.expires_at(expires));
+                Ok(data)
+            }
+            (Fetched::NotModified, Some((cached, data))) => {
+                self.cache.put(url, &cached.body, &cached.validators, ttl.expires_at(expires));
+                Ok(data)
+            }
+            (Fetched::NotModified, None) => Err(WeatherError::Api(format!(
+                "HTTP 304 Not Modified for {} without a cached response",
+                url
+            ))),
+        }
+    }
+
+    async fn fetch_with_retry(
+        &self,
+        url: &str,
+        validators: Option<&Validators>,
+        attempts: u32,
+    ) -> Result<(Fetched, Option<DateTime<Utc>>), WeatherError> {
         let mut last_error = None;
         
-        for attempt in 1..=MAX_RETRIES {
-            match self.client.get(url).send().await {
+        for attempt in 1..=attempts {
+            let mut request = self.client.get(url);
+            if let Some(validators) = validators {
+                if let Some(etag) = &validators.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &validators.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            match request.send().await {
                 Ok(response) => {
-                    if response.status().is_success() {
-                        match response.json::<T>().await {
-                            Ok(data) => return Ok(data),
+                    let header = |name| response.headers()
+                        .get(name)
+                        .and_then(|value: &HeaderValue| value.to_str().ok())
+                        .map(str::to_string);
+                    let expires = header(EXPIRES)
+                        .and_then(|value| DateTime::parse_from_rfc2822(&value).ok())
+                        .map(|time| time.with_timezone(&Utc));
+
+                    if response.status() == StatusCode::NOT_MODIFIED {
+                        return Ok((Fetched::NotModified, expires));
+                    } else if response.status().is_success() {
+                        let validators = Validators {
+                            etag: header(ETAG),
+                            last_modified: header(LAST_MODIFIED),
+                        };
+                        match response.text().await {
+                            Ok(body) => return Ok((Fetched::Modified { body, validators }, expires)),
                             Err(e) => last_error = Some(WeatherError::Network(e)),
                         }
                     } else {
-                        last_error = Some(WeatherError::Api(format!(
+                        let status = response.status();
+                        let error = WeatherError::Api(format!(
                             "HTTP {}: {}", 
-                            response.status(), 
+                            status, 
                             response.text().await.unwrap_or_default()
-                        )));
+                        ));
+                        // Client errors won't fix themselves, except rate limiting
+                        if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS {
+                            return Err(error);
+                        }
+                        last_error = Some(error);
                     }
                 }
                 Err(e) => last_error = Some(WeatherError::Network(e)),
             }
             
-            if attempt < MAX_RETRIES {
+            if attempt < attempts {
                 sleep(Duration::from_millis(RETRY_DELAY_MS * attempt as u64)).await;
             }
         }
@@ -110,7 +261,7 @@ impl WeatherClient {
         }
 
         let url = format!("https://api.zippopotam.us/us/{}", zip);
-        let response: ZippopotamResponse = self.get_with_retry(&url).await?;
+        let response: ZippopotamResponse = self.get_with_retry(&url, CacheTtl::Fixed(ZIP_CACHE_TTL)).await?;
         
         let place = response.places.first()
             .ok_or(WeatherError::LocationNotFound)?;
@@ -132,22 +283,77 @@ impl WeatherClient {
         Ok(())
     }
 
-    pub async fn get_weather_data(&self, location: &Location) -> Result<WeatherData, WeatherError> {
-        // Get NWS grid info and forecast concurrently
+    async fn get_point(&self, location: &Location) -> Result<NWSPointProperties, WeatherError> {
         let point_url = format!("https://api.weather.gov/points/{},{}", location.lat, location.lon);
-        let nws_point: NWSPointResponse = self.get_with_retry(&point_url).await?;
-        
-        let forecast_url = format!(
+        let nws_point: NWSPointResponse = self.get_with_retry(&point_url, CacheTtl::Fixed(POINT_CACHE_TTL)).await?;
+        Ok(nws_point.properties)
+    }
+
+    fn forecast_url(point: &NWSPointProperties) -> String {
+        format!(
             "https://api.weather.gov/gridpoints/{}/{},{}/forecast",
-            nws_point.properties.grid_id,
-            nws_point.properties.grid_x,
-            nws_point.properties.grid_y
-        );
+            point.grid_id,
+            point.grid_x,
+            point.grid_y
+        )
+    }
+
+    pub async fn get_forecast(&self, location: &Location) -> Result<Vec<ForecastPeriod>, WeatherError> {
+        let point = self.get_point(location).await?;
+        let forecast: ForecastResponse = self.get_with_retry(&Self::forecast_url(&point), CacheTtl::Expires(FORECAST_CACHE_TTL)).await?;
 
-        // Get forecast and stations info concurrently
-        let (forecast_result, stations_result) = tokio::join!(
-            self.get_with_retry::<ForecastResponse>(&forecast_url),
-            self.get_with_retry::<StationsResponse>(&nws_point.properties.observation_stations)
+        if forecast.properties.periods.is_empty() {
+            return Err(WeatherError::NoWeatherData);
+        }
+
+        Ok(forecast.properties.periods)
+    }
+
+    pub async fn get_hourly_forecast(&self, location: &Location, hours: u32) -> Result<Vec<ForecastPeriod>, WeatherError> {
+        let point = self.get_point(location).await?;
+        let forecast: ForecastResponse = self.get_with_retry(&point.forecast_hourly, CacheTtl::Expires(FORECAST_CACHE_TTL)).await?;
+
+        // A cached or not yet regenerated forecast can start with hours that are already over
+        let now = Utc::now();
+        let periods: Vec<ForecastPeriod> = forecast.properties.periods.into_iter()
+            .filter(|period| period.end().map_or(true, |end| end > now))
+            .take(hours as usize)
+            .collect();
+        if periods.is_empty() {
+            return Err(WeatherError::NoWeatherData);
+        }
+
+        Ok(periods)
+    }
+
+    /// Raw gridpoint layers for the location's forecast grid cell.
+    pub async fn get_gridpoint(&self, location: &Location) -> Result<GridpointData, WeatherError> {
+        let point = self.get_point(location).await?;
+        self.get_point_gridpoint(&point).await
+    }
+
+    async fn get_point_gridpoint(&self, point: &NWSPointProperties) -> Result<GridpointData, WeatherError> {
+        let url = format!("https://api.weather.gov/gridpoints/{}/{},{}", point.grid_id, point.grid_x, point.grid_y);
+        let response: GridpointResponse = self.get_with_retry(&url, CacheTtl::Expires(FORECAST_CACHE_TTL)).await?;
+        GridpointData::from_response(response)
+    }
+
+    pub async fn get_active_alerts(&self, location: &Location) -> Result<Vec<Alert>, WeatherError> {
+        let url = format!("https://api.weather.gov/alerts/active?point={:.4},{:.4}", location.lat, location.lon);
+        let response: AlertsResponse = self.get_with_retry(&url, CacheTtl::Fixed(ALERTS_CACHE_TTL)).await?;
+        Ok(response.features.into_iter().map(|feature| feature.properties).collect())
+    }
+
+    pub async fn get_weather_data(&self, location: &Location) -> Result<WeatherData, WeatherError> {
+        let point = self.get_point(location).await?;
+        let forecast_url = Self::forecast_url(&point);
+
+        // Get forecast, stations info, alerts and gridpoint concurrently
+        let (forecast_result, stations_result, alerts_result, gridpoint_result) = tokio::join!(
+            self.get_with_retry::<ForecastResponse>(&forecast_url, CacheTtl::Expires(FORECAST_CACHE_TTL)),
+            self.get_observation_stations(location, &point),
+            self.get_active_alerts(location),
+            self.get_point_gridpoint(&point)
         );
 
         let forecast = forecast_result?;
@@ -155,40 +361,187 @@ impl WeatherClient {
 
         let first_period = forecast.properties.periods.first()
             .ok_or(WeatherError::NoWeatherData)?;
+        let (high, low) = forecast.properties.high_low()?;
 
         let mut weather_data = WeatherData {
-            temperature: first_period.temperature,
+            temperature: first_period.temperature_celsius()?,
             condition: first_period.short_forecast.clone(),
+            icon: first_period.icon.clone(),
             humidity: None,
             wind_speed: None,
             wind_direction: None,
+            dewpoint: None,
+            heat_index: None,
+            wind_chill: None,
+            feels_like: None,
+            wind_gust: None,
+            pressure: None,
+            sea_level_pressure: None,
+            visibility: None,
+            cloud_layers: Vec::new(),
+            precipitation_last_hour: None,
+            precipitation_last_3_hours: None,
+            precipitation_last_6_hours: None,
+            max_temperature_24h: None,
+            min_temperature_24h: None,
+            description: None,
+            high,
+            low,
+            precipitation_chance: first_period.precipitation_chance(),
+            temperature_trend: None,
+            pressure_trend: None,
+            station: None,
+            observed_at: None,
+            // Alerts are supplementary; a failed alerts request shouldn't hide the weather
+            alerts: alerts_result.unwrap_or_default(),
+            // Likewise for accumulations, which need the gridpoint layers
+            accumulations: gridpoint_result
+                .map(|gridpoint| {
+                    let now = Utc::now();
+                    ACCUMULATION_WINDOWS.iter().filter_map(|&hours| gridpoint.accumulation(now, hours).ok()).collect()
+                })
+                .unwrap_or_default(),
+            updated: Utc::now(),
         };
 
-        // Convert temperature to Celsius if forecast is in Fahrenheit
-        if first_period.temperature_unit == "F" {
-            weather_data.temperature = ((first_period.temperature as f64 - CELSIUS_TO_FAHRENHEIT_OFFSET) / CELSIUS_TO_FAHRENHEIT_MULTIPLIER).round() as i64;
+        // Use the nearest station with a recent, usable observation; the
+        // forecast values stay if none of them has one
+        for station in stations.into_iter().take(MAX_STATION_ATTEMPTS) {
+            let Ok(observation) = self.get_current_observation(&station.id).await else {
+                continue;
+            };
+            if !self.is_usable(&observation.properties) {
+                continue;
+            }
+            Self::apply_observation(&mut weather_data, observation.properties)?;
+            weather_data.station = Some(station);
+            break;
         }
 
-        // Try to get current observations for more accurate data
-        if let Some(station) = stations.features.first() {
-            if let Ok(observation) = self.get_current_observation(&station.properties.station_identifier).await {
-                if let Some(temp_c) = observation.properties.temperature.value {
-                    weather_data.temperature = temp_c.round() as i64;
-                }
-                weather_data.humidity = observation.properties.relative_humidity
-                    .and_then(|h| h.value);
-                weather_data.wind_speed = observation.properties.wind_speed
-                    .and_then(|w| w.value);
-                weather_data.wind_direction = observation.properties.wind_direction
-                    .and_then(|w| w.value);
+        Ok(weather_data)
+    }
+
+    /// Readings from the last `hours` hours at the pinned or nearest station
+    /// that has any.
+    pub async fn get_observation_history(&self, location: &Location, hours: u32) -> Result<History, WeatherError> {
+        let point = self.get_point(location).await?;
+        let stations = self.get_observation_stations(location, &point).await?;
+
+        let mut last_error = WeatherError::NoWeatherData;
+        for station in stations.into_iter().take(MAX_STATION_ATTEMPTS) {
+            match self.get_station_history(station, hours).await {
+                Ok(history) if !history.points.is_empty() => return Ok(history),
+                Ok(_) => {}
+                Err(e) => last_error = e,
             }
         }
+        Err(last_error)
+    }
 
-        Ok(weather_data)
+    pub async fn get_station_history(&self, station: Station, hours: u32) -> Result<History, WeatherError> {
+        let since = Utc::now() - chrono::Duration::hours(hours.into());
+        // Start on the hour so calls within the same hour share a cache entry
+        let start = since.duration_trunc(chrono::Duration::hours(1)).unwrap_or(since);
+        let url = format!(
+            "https://api.weather.gov/stations/{}/observations?start={}",
+            station.id,
+            start.format("%Y-%m-%dT%H:%M:%SZ")
+        );
+        let response: ObservationCollection = self.get_with_retry(&url, CacheTtl::Fixed(OBSERVATION_CACHE_TTL)).await?;
+
+        let pascal = |pressure: &Option<ObservationValue<f64>>| {
+            pressure.as_ref().map(|p| p.value_in(Unit::Pascal)).transpose().map(Option::flatten)
+        };
+        let mut readings = Vec::new();
+        for feature in response.features {
+            let properties = feature.properties;
+            let Some(time) = properties.timestamp.map(|t| t.with_timezone(&Utc)).filter(|time| *time >= since) else {
+                continue;
+            };
+            readings.push(Reading {
+                time,
+                temperature: properties.temperature.value_in(Unit::Celsius)?,
+                sea_level_pressure: pascal(&properties.sea_level_pressure)?,
+                station_pressure: pascal(&properties.barometric_pressure)?,
+            });
+        }
+
+        Ok(History::from_readings(station, readings))
+    }
+
+    /// Candidate stations nearest first, or just the pinned station.
+    async fn get_observation_stations(&self, location: &Location, point: &NWSPointProperties) -> Result<Vec<Station>, WeatherError> {
+        if let Some(id) = &self.station {
+            let url = format!("https://api.weather.gov/stations/{}", id);
+            let station: StationFeature = self.get_with_retry(&url, CacheTtl::Fixed(STATIONS_CACHE_TTL)).await?;
+            return Ok(vec![station.to_station(location)]);
+        }
+
+        let response: StationsResponse = self
+            .get_with_retry(&point.observation_stations, CacheTtl::Fixed(STATIONS_CACHE_TTL))
+            .await?;
+        let mut stations: Vec<Station> = response.features.iter().map(|feature| feature.to_station(location)).collect();
+        // Stations without coordinates keep their API order, after the rest
+        stations.sort_by(|a, b| a.distance_km.unwrap_or(f64::MAX).total_cmp(&b.distance_km.unwrap_or(f64::MAX)));
+        Ok(stations)
+    }
+
+    /// An observation is usable if it has a temperature and isn't too old.
+    fn is_usable(&self, properties: &ObservationProperties) -> bool {
+        let max_age = chrono::Duration::from_std(self.max_observation_age).unwrap_or(chrono::Duration::MAX);
+        properties.temperature.value.is_some()
+            && properties.timestamp.is_some_and(|timestamp| Utc::now() - timestamp.with_timezone(&Utc) <= max_age)
+    }
+
+    /// Copies observed values into `weather_data`, normalizing them to °C,
+    /// km/h, Pa, m and mm regardless of the units the station reported.
+    fn apply_observation(weather_data: &mut WeatherData, properties: ObservationProperties) -> Result<(), WeatherError> {
+        let value_in = |value: &Option<ObservationValue<f64>>, unit: Unit| match value {
+            Some(value) => value.value_in(unit),
+            None => Ok(None),
+        };
+
+        weather_data.humidity = value_in(&properties.relative_humidity, Unit::Percent)?;
+        weather_data.wind_speed = value_in(&properties.wind_speed, Unit::KilometersPerHour)?;
+        weather_data.wind_direction = value_in(&properties.wind_direction, Unit::Degree)?;
+        weather_data.wind_gust = value_in(&properties.wind_gust, Unit::KilometersPerHour)?;
+        weather_data.pressure = value_in(&properties.barometric_pressure, Unit::Pascal)?;
+        weather_data.sea_level_pressure = value_in(&properties.sea_level_pressure, Unit::Pascal)?;
+        weather_data.visibility = value_in(&properties.visibility, Unit::Meter)?;
+        weather_data.cloud_layers = properties.cloud_layers.unwrap_or_default()
+            .into_iter()
+            .map(|layer| Ok(CloudLayer { base: layer.base.value_in(Unit::Meter)?, amount: layer.amount }))
+            .collect::<Result<_, WeatherError>>()?;
+        weather_data.precipitation_last_hour = value_in(&properties.precipitation_last_hour, Unit::Millimeter)?;
+        weather_data.precipitation_last_3_hours = value_in(&properties.precipitation_last_3_hours, Unit::Millimeter)?;
+        weather_data.precipitation_last_6_hours = value_in(&properties.precipitation_last_6_hours, Unit::Millimeter)?;
+        weather_data.max_temperature_24h = value_in(&properties.max_temperature_last_24_hours, Unit::Celsius)?;
+        weather_data.min_temperature_24h = value_in(&properties.min_temperature_last_24_hours, Unit::Celsius)?;
+        weather_data.description = properties.text_description.filter(|d| !d.is_empty());
+        weather_data.observed_at = properties.timestamp.map(|t| t.with_timezone(&Utc));
+
+        if let Some(temp_c) = properties.temperature.value_in(Unit::Celsius)? {
+            weather_data.temperature = temp_c;
+
+            // Prefer reported values, deriving any the station left out
+            let humidity = weather_data.humidity;
+            let wind_speed = weather_data.wind_speed;
+            weather_data.dewpoint = value_in(&properties.dewpoint, Unit::Celsius)?
+                .or_else(|| humidity.and_then(|rh| comfort::dew_point(temp_c, rh)));
+            weather_data.heat_index = value_in(&properties.heat_index, Unit::Celsius)?
+                .or_else(|| humidity.and_then(|rh| comfort::heat_index(temp_c, rh)));
+            weather_data.wind_chill = value_in(&properties.wind_chill, Unit::Celsius)?
+                .or_else(|| wind_speed.and_then(|w| comfort::wind_chill(temp_c, w)));
+            weather_data.feels_like = Some(comfort::feels_like(temp_c, weather_data.heat_index, weather_data.wind_chill));
+        }
+
+        Ok(())
     }
 
+    /// A single attempt: with other stations to fall back on, moving on beats
+    /// retrying one that is down.
     async fn get_current_observation(&self, station_id: &str) -> Result<ObservationResponse, WeatherError> {
         let url = format!("https://api.weather.gov/stations/{}/observations/latest", station_id);
-        self.get_with_retry(&url).await
+        self.get_with_attempts(&url, CacheTtl::Fixed(OBSERVATION_CACHE_TTL), 1).await
     }
 }
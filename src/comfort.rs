@@ -0,0 +1,128 @@
+// Derived comfort metrics using the formulas from the NWS Weather Prediction
+// Center. Inputs and outputs are in °C and km/h, matching the observation API.
+
+const HEAT_INDEX_MIN_F: f64 = 80.0;
+const WIND_CHILL_MAX_F: f64 = 50.0;
+const WIND_CHILL_MIN_MPH: f64 = 3.0;
+
+const KMH_TO_MPH: f64 = 0.621371;
+
+fn to_fahrenheit(temp_c: f64) -> f64 {
+    temp_c * 9.0 / 5.0 + 32.0
+}
+
+fn to_celsius(temp_f: f64) -> f64 {
+    (temp_f - 32.0) * 5.0 / 9.0
+}
+
+/// Dew point from temperature and relative humidity (Magnus formula).
+pub fn dew_point(temp_c: f64, relative_humidity: f64) -> Option<f64> {
+    const A: f64 = 17.625;
+    const B: f64 = 243.04;
+
+    if relative_humidity <= 0.0 {
+        return None;
+    }
+
+    let gamma = (relative_humidity / 100.0).ln() + A * temp_c / (B + temp_c);
+    Some(B * gamma / (A - gamma))
+}
+
+/// NWS heat index (Rothfusz regression with Steadman's adjustments), or
+/// `None` below 80°F where it isn't meaningful.
+pub fn heat_index(temp_c: f64, relative_humidity: f64) -> Option<f64> {
+    let t = to_fahrenheit(temp_c);
+    let rh = relative_humidity;
+
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < HEAT_INDEX_MIN_F {
+        return (t >= HEAT_INDEX_MIN_F).then(|| to_celsius(simple));
+    }
+
+    let mut hi = -42.379 + 2.04901523 * t + 10.14333127 * rh
+        - 0.22475541 * t * rh
+        - 0.00683783 * t * t
+        - 0.05481717 * rh * rh
+        + 0.00122874 * t * t * rh
+        + 0.00085282 * t * rh * rh
+        - 0.00000199 * t * t * rh * rh;
+
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        hi -= ((13.0 - rh) / 4.0) * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        hi += ((rh - 85.0) / 10.0) * ((87.0 - t) / 5.0);
+    }
+
+    Some(to_celsius(hi))
+}
+
+/// NWS wind chill, or `None` above 50°F or below 3 mph where it's undefined.
+pub fn wind_chill(temp_c: f64, wind_speed_kmh: f64) -> Option<f64> {
+    let t = to_fahrenheit(temp_c);
+    let v = wind_speed_kmh * KMH_TO_MPH;
+
+    if t > WIND_CHILL_MAX_F || v < WIND_CHILL_MIN_MPH {
+        return None;
+    }
+
+    let v016 = v.powf(0.16);
+    Some(to_celsius(35.74 + 0.6215 * t - 35.75 * v016 + 0.4275 * t * v016))
+}
+
+/// Apparent temperature: wind chill when cold and windy, heat index when hot
+/// and humid, otherwise the air temperature.
+pub fn feels_like(temp_c: f64, heat_index: Option<f64>, wind_chill: Option<f64>) -> f64 {
+    wind_chill.or(heat_index).unwrap_or(temp_c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mph_to_kmh(mph: f64) -> f64 {
+        mph / KMH_TO_MPH
+    }
+
+    #[test]
+    fn heat_index_matches_nws_table() {
+        let hi = heat_index(to_celsius(95.0), 50.0).unwrap();
+        assert!((to_fahrenheit(hi) - 105.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn heat_index_needs_80_f() {
+        assert_eq!(heat_index(to_celsius(79.0), 50.0), None);
+        assert!(heat_index(to_celsius(80.0), 40.0).is_some());
+    }
+
+    #[test]
+    fn wind_chill_matches_nws_table() {
+        // The NWS chart lists -2°F for 15°F at 20 mph; 14°F works out to about -3.6°F
+        let chill = wind_chill(to_celsius(15.0), mph_to_kmh(20.0)).unwrap();
+        assert!((to_fahrenheit(chill) - -2.0).abs() < 0.5);
+        let chill = wind_chill(to_celsius(14.0), mph_to_kmh(20.0)).unwrap();
+        assert!((to_fahrenheit(chill) - -3.6).abs() < 0.1);
+    }
+
+    #[test]
+    fn wind_chill_cutoffs() {
+        assert_eq!(wind_chill(to_celsius(51.0), mph_to_kmh(20.0)), None);
+        assert_eq!(wind_chill(to_celsius(14.0), mph_to_kmh(2.9)), None);
+        assert!(wind_chill(to_celsius(50.0), mph_to_kmh(3.0)).is_some());
+    }
+
+    #[test]
+    fn dew_point_from_humidity() {
+        assert!((dew_point(20.0, 50.0).unwrap() - 9.26).abs() < 0.01);
+        assert!((dew_point(15.0, 100.0).unwrap() - 15.0).abs() < 1e-9);
+        assert_eq!(dew_point(20.0, 0.0), None);
+        assert_eq!(dew_point(20.0, -5.0), None);
+    }
+
+    #[test]
+    fn feels_like_prefers_wind_chill() {
+        assert_eq!(feels_like(20.0, None, None), 20.0);
+        assert_eq!(feels_like(30.0, Some(33.0), None), 33.0);
+        assert_eq!(feels_like(-5.0, Some(1.0), Some(-10.0)), -10.0);
+    }
+}
@@ -0,0 +1,192 @@
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WeatherCondition {
+    Clear,
+    FewClouds,
+    PartlyCloudy,
+    MostlyCloudy,
+    Overcast,
+    Rain,
+    Showers,
+    Thunderstorm,
+    Snow,
+    Sleet,
+    FreezingRain,
+    Blizzard,
+    Fog,
+    Haze,
+    Smoke,
+    Dust,
+    Wind,
+    Hot,
+    Cold,
+    Tornado,
+    TropicalStorm,
+    Hurricane,
+    Unknown,
+}
+
+impl WeatherCondition {
+    /// Classifies from the NWS icon URL when possible, falling back to the
+    /// forecast or observation text.
+    pub fn classify(icon_url: Option<&str>, text: &str) -> Self {
+        icon_url
+            .and_then(Self::from_icon_url)
+            .unwrap_or_else(|| Self::from_text(text))
+    }
+
+    /// Parses icon URLs such as
+    /// `https://api.weather.gov/icons/land/day/tsra,40/sct?size=medium`.
+    /// When the URL holds two codes (a "then" transition), the first one
+    /// describes the start of the period and wins.
+    pub fn from_icon_url(url: &str) -> Option<Self> {
+        let path = url.split(['?', '#']).next()?;
+        let mut segments = path.split('/').skip_while(|segment| *segment != "day" && *segment != "night");
+        segments.next()?;
+        let code = segments.next()?.split(',').next()?;
+        Self::from_icon_code(code)
+    }
+
+    pub fn from_icon_code(code: &str) -> Option<Self> {
+        // Older icon URLs prefix night variants with "n", e.g. "nskc"
+        let code = match code {
+            "nskc" | "nfew" | "nsct" | "nbkn" | "novc" => &code[1..],
+            _ => code,
+        };
+
+        let condition = match code {
+            "skc" => WeatherCondition::Clear,
+            "few" => WeatherCondition::FewClouds,
+            "sct" => WeatherCondition::PartlyCloudy,
+            "bkn" => WeatherCondition::MostlyCloudy,
+            "ovc" => WeatherCondition::Overcast,
+            "wind_skc" | "wind_few" | "wind_sct" | "wind_bkn" | "wind_ovc" | "wind" => WeatherCondition::Wind,
+            "snow" | "rain_snow" | "sn" | "rasn" | "mix" => WeatherCondition::Snow,
+            "rain_sleet" | "snow_sleet" | "sleet" | "ip" | "raip" => WeatherCondition::Sleet,
+            "fzra" | "rain_fzra" | "snow_fzra" | "fzrara" => WeatherCondition::FreezingRain,
+            "rain" | "ra" | "minus_ra" => WeatherCondition::Rain,
+            "rain_showers" | "rain_showers_hi" | "shra" | "hi_shwrs" => WeatherCondition::Showers,
+            "tsra" | "tsra_sct" | "tsra_hi" | "scttsra" | "hi_tsra" => WeatherCondition::Thunderstorm,
+            "tornado" | "fc" => WeatherCondition::Tornado,
+            "hurricane" | "hur_warn" | "hur_watch" => WeatherCondition::Hurricane,
+            "tropical_storm" | "ts_warn" | "ts_watch" | "ts_nowarn" => WeatherCondition::TropicalStorm,
+            "dust" | "du" => WeatherCondition::Dust,
+            "smoke" | "fu" => WeatherCondition::Smoke,
+            "haze" | "hz" => WeatherCondition::Haze,
+            "hot" => WeatherCondition::Hot,
+            "cold" => WeatherCondition::Cold,
+            "blizzard" => WeatherCondition::Blizzard,
+            "fog" | "fg" => WeatherCondition::Fog,
+            _ => return None,
+        };
+        Some(condition)
+    }
+
+    /// Parses forecast text like "Chance Rain Showers then Thunderstorms".
+    /// Only the part before "then" is used, since it describes the start of
+    /// the period. Hazardous and precipitating weather is checked before sky
+    /// cover so "Mostly Sunny with Isolated Thunderstorms" isn't "sunny".
+    pub fn from_text(text: &str) -> Self {
+        let text = text.to_lowercase();
+        let text = text.split(" then ").next().unwrap_or_default();
+        let has = |words: &[&str]| words.iter().any(|word| text.contains(word));
+
+        if has(&["tornado", "funnel cloud"]) {
+            WeatherCondition::Tornado
+        } else if has(&["hurricane"]) {
+            WeatherCondition::Hurricane
+        } else if has(&["tropical storm"]) {
+            WeatherCondition::TropicalStorm
+        } else if has(&["blizzard"]) {
+            WeatherCondition::Blizzard
+        } else if has(&["thunder", "t-storm", "tstorm"]) {
+            WeatherCondition::Thunderstorm
+        } else if has(&["freezing rain", "freezing drizzle"]) {
+            WeatherCondition::FreezingRain
+        } else if has(&["sleet", "ice pellets"]) {
+            WeatherCondition::Sleet
+        } else if has(&["snow", "flurries"]) {
+            WeatherCondition::Snow
+        } else if has(&["shower"]) {
+            WeatherCondition::Showers
+        } else if has(&["rain", "drizzle"]) {
+            WeatherCondition::Rain
+        } else if has(&["fog", "mist"]) {
+            WeatherCondition::Fog
+        } else if has(&["smoke"]) {
+            WeatherCondition::Smoke
+        } else if has(&["dust", "sand"]) {
+            WeatherCondition::Dust
+        } else if has(&["haze"]) {
+            WeatherCondition::Haze
+        } else if has(&["mostly cloudy", "considerable cloud"]) {
+            WeatherCondition::MostlyCloudy
+        } else if has(&["partly cloudy", "partly sunny", "partly clear"]) {
+            WeatherCondition::PartlyCloudy
+        } else if has(&["mostly sunny", "mostly clear", "few clouds"]) {
+            WeatherCondition::FewClouds
+        } else if has(&["overcast", "cloudy"]) {
+            WeatherCondition::Overcast
+        } else if has(&["sunny", "clear", "fair"]) {
+            WeatherCondition::Clear
+        } else if has(&["wind", "breezy", "blustery", "gust"]) {
+            WeatherCondition::Wind
+        } else if has(&["hot"]) {
+            WeatherCondition::Hot
+        } else if has(&["cold", "frigid"]) {
+            WeatherCondition::Cold
+        } else {
+            WeatherCondition::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sky_cover_text_is_reachable() {
+        assert_eq!(WeatherCondition::from_text("Mostly Sunny"), WeatherCondition::FewClouds);
+        assert_eq!(WeatherCondition::from_text("Partly Cloudy"), WeatherCondition::PartlyCloudy);
+        assert_eq!(WeatherCondition::from_text("Partly Sunny"), WeatherCondition::PartlyCloudy);
+        assert_eq!(WeatherCondition::from_text("Mostly Cloudy"), WeatherCondition::MostlyCloudy);
+        assert_eq!(WeatherCondition::from_text("Cloudy"), WeatherCondition::Overcast);
+        assert_eq!(WeatherCondition::from_text("Sunny"), WeatherCondition::Clear);
+    }
+
+    #[test]
+    fn text_uses_the_start_of_the_period() {
+        assert_eq!(
+            WeatherCondition::from_text("Chance Rain Showers then Thunderstorms"),
+            WeatherCondition::Showers
+        );
+        assert_eq!(WeatherCondition::from_text("Sunny then Chance Rain"), WeatherCondition::Clear);
+    }
+
+    #[test]
+    fn weather_beats_sky_cover_in_text() {
+        assert_eq!(
+            WeatherCondition::from_text("Mostly Sunny with Isolated Thunderstorms"),
+            WeatherCondition::Thunderstorm
+        );
+        assert_eq!(WeatherCondition::from_text("Partly Cloudy and Light Snow"), WeatherCondition::Snow);
+        assert_eq!(WeatherCondition::from_text("Something new"), WeatherCondition::Unknown);
+    }
+
+    #[test]
+    fn icon_urls_use_the_first_code() {
+        assert_eq!(
+            WeatherCondition::from_icon_url("https://api.weather.gov/icons/land/day/rain_showers,30/tsra_hi,30?size=medium"),
+            Some(WeatherCondition::Showers)
+        );
+        assert_eq!(
+            WeatherCondition::from_icon_url("https://api.weather.gov/icons/land/night/nskc?size=medium"),
+            Some(WeatherCondition::Clear)
+        );
+        assert_eq!(WeatherCondition::from_icon_url("https://api.weather.gov/icons/land/day/unknown_code"), None);
+        assert_eq!(WeatherCondition::from_icon_url("https://example.com/no-period"), None);
+    }
+}
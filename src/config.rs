@@ -1,47 +1,408 @@
-use clap::Parser;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config_file::ConfigFile;
+use crate::error::WeatherError;
+use crate::units::{convert, Unit};
+
+const MIN_INTERVAL: Duration = Duration::from_secs(30);
+// Upper bound for --hours; the forecast and observation APIs cover about ten days at most
+const MAX_HOURS: i64 = 240;
 
 #[derive(Parser)]
 #[command(author, version, about = "Get weather information for Waybar")]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// ZIP code, e.g. 90210
-    #[arg(long, conflicts_with_all=&["lat", "lon"])]
+    #[arg(long, global = true, conflicts_with_all=&["lat", "lon"])]
     pub zip: Option<String>,
 
     /// Latitude, e.g. 37.9
-    #[arg(long, requires = "lon")]
+    #[arg(long, global = true, requires = "lon")]
     pub lat: Option<f64>,
 
     /// Longitude, e.g. -122.3
-    #[arg(long, requires = "lat")]
+    #[arg(long, global = true, requires = "lat")]
     pub lon: Option<f64>,
 
-    /// Temperature unit (F or C)
-    #[arg(long, default_value = "F", value_parser = parse_unit)]
-    pub unit: TemperatureUnit,
+    /// Named location from the config file, e.g. home
+    #[arg(long, global = true, conflicts_with_all=&["zip", "lat", "lon"])]
+    pub location: Option<String>,
+
+    /// Observation station to use instead of the nearest, e.g. KSFO
+    #[arg(long, global = true)]
+    pub station: Option<String>,
+
+    /// Ignore station readings older than this and use the forecast instead (e.g. 90m, 2h)
+    #[arg(long, global = true, default_value = "2h", value_parser = parse_duration)]
+    pub max_observation_age: Duration,
+
+    /// Config file (default: $XDG_CONFIG_HOME/nws-weather/config.toml)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Temperature unit (F or C); defaults to the unit system's
+    #[arg(long, global = true, value_parser = parse_unit)]
+    pub unit: Option<TemperatureUnit>,
+
+    /// Decimal places for temperatures (0 or 1)
+    #[arg(long, global = true, default_value = "0", value_parser = parse_precision)]
+    pub precision: usize,
+
+    /// Unit preset: imperial (default), metric, si or uk
+    #[arg(long, global = true, value_parser = parse_unit_system)]
+    pub unit_system: Option<UnitSystem>,
+
+    /// Wind speed unit: mph, kmh, ms, kt or bft (Beaufort)
+    #[arg(long, global = true, value_parser = parse_wind_unit)]
+    pub wind_unit: Option<WindUnit>,
+
+    /// Pressure unit: inHg, hPa, mb or mmHg
+    #[arg(long, global = true, value_parser = parse_pressure_unit)]
+    pub pressure_unit: Option<PressureUnit>,
+
+    /// Visibility and cloud height unit: mi or km
+    #[arg(long, global = true, value_parser = parse_distance_unit)]
+    pub distance_unit: Option<DistanceUnit>,
+
+    /// Precipitation unit: in or mm
+    #[arg(long, global = true, value_parser = parse_precip_unit)]
+    pub precip_unit: Option<PrecipUnit>,
 
     /// Icon set to use
-    #[arg(long, default_value = "nerdfont", value_parser = parse_icon_set)]
+    #[arg(long, global = true, default_value = "nerdfont", value_parser = parse_icon_set)]
     pub icons: IconSet,
 
     /// Include additional weather details in tooltip
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub detailed: bool,
 
     /// Wait for network connectivity before starting
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub wait_for_network: bool,
 
+    /// Bypass the on-disk response cache
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// Show last known weather for up to this long when a refresh fails (e.g. 90m, 6h)
+    #[arg(long, global = true, default_value = "6h", value_parser = parse_duration)]
+    pub max_stale: Duration,
+
+    /// Template for the bar text, e.g. "{icon} {temp}{unit}[ {humidity}%]"
+    #[arg(long, global = true)]
+    pub text_template: Option<String>,
+
+    /// Template for the tooltip; "\n" starts a new line
+    #[arg(long, global = true)]
+    pub tooltip_template: Option<String>,
+
+    /// Show temperature trend arrows from the last few hours of observations
+    #[arg(long, global = true)]
+    pub trend: bool,
+
+    /// Snowfall over the next 24 hours that counts as heavy (e.g. 6in, 15cm)
+    #[arg(long, global = true, default_value = "6in", value_parser = parse_depth)]
+    pub heavy_snow: f64,
+
+    /// Ice accumulation over the next 24 hours that counts as heavy
+    #[arg(long, global = true, default_value = "0.25in", value_parser = parse_depth)]
+    pub heavy_ice: f64,
+
+    /// Liquid precipitation over the next 24 hours that counts as heavy
+    #[arg(long, global = true, default_value = "1in", value_parser = parse_depth)]
+    pub heavy_rain: f64,
+
+    /// Keep running and print a new line on every refresh (Waybar continuous mode)
+    #[arg(long, global = true)]
+    pub daemon: bool,
+
+    /// Refresh interval in daemon mode (e.g. 30s, 10m, 1h)
+    #[arg(long, global = true, default_value = "10m", value_parser = parse_interval, requires = "daemon")]
+    pub interval: Duration,
+
     /// Output format
-    #[arg(long, default_value = "waybar", value_parser = parse_output_format)]
+    #[arg(long, global = true, default_value = "waybar", value_parser = parse_output_format)]
     pub format: OutputFormat,
 }
 
+#[derive(Subcommand, Clone, Debug)]
+pub enum Command {
+    /// Show the multi-day forecast (all day and night periods)
+    Forecast,
+    /// Show the hourly forecast for the next few hours
+    Hourly {
+        /// Number of hours to show
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..=MAX_HOURS))]
+        hours: u32,
+    },
+    /// Show active weather alerts for the location
+    Alerts,
+    /// Show recent observations with high/low and temperature and pressure trends
+    History {
+        /// Number of hours of observations to fetch
+        #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u32).range(1..=MAX_HOURS))]
+        hours: u32,
+    },
+    /// Show raw forecast grid layers, or one layer hour by hour
+    Gridpoint {
+        /// Layer to show, e.g. temperature or skyCover; lists layers if omitted
+        #[arg(long)]
+        layer: Option<String>,
+        /// Number of hours to show
+        #[arg(long, default_value_t = 24, value_parser = clap::value_parser!(u32).range(1..=MAX_HOURS))]
+        hours: u32,
+    },
+    /// Check whether precipitation is expected soon; exits 0 if so, 1 if not
+    /// and 2 on errors
+    RainCheck {
+        /// Number of hours to check
+        #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..=MAX_HOURS))]
+        hours: u32,
+        /// Chance of precipitation, in percent, that counts as expected
+        #[arg(long, default_value_t = 50.0)]
+        threshold: f64,
+    },
+    /// Manage the on-disk response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum CacheCommand {
+    /// Delete all cached responses
+    Clear,
+}
+
+impl Args {
+    /// Parses the command line alone, so the command is known even if the
+    /// config file turns out to be broken.
+    pub fn parse_command_line() -> (Self, ArgMatches) {
+        let matches = Self::command().get_matches();
+        let args = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        (args, matches)
+    }
+
+    /// Merges in the config file for settings not given on the command line.
+    pub fn apply_config(&mut self, matches: &ArgMatches) -> Result<(), WeatherError> {
+        ConfigFile::discover(self)?.apply(self, matches)
+    }
+
+    /// Exit status for failures; rain-check uses 0 and 1 for its answer.
+    pub fn error_exit_code(&self) -> i32 {
+        if matches!(self.command, Some(Command::RainCheck { .. })) { 2 } else { 1 }
+    }
+
+    pub fn requires_location(&self) -> bool {
+        !matches!(self.command, Some(Command::Cache { .. }))
+    }
+
+    /// Units for display: the unit system's presets with any per-quantity
+    /// overrides applied.
+    pub fn units(&self) -> Units {
+        let preset = Units::for_system(self.unit_system.as_ref().unwrap_or(&UnitSystem::Imperial));
+        Units {
+            temperature: self.unit.clone().unwrap_or(preset.temperature),
+            wind: self.wind_unit.clone().unwrap_or(preset.wind),
+            pressure: self.pressure_unit.clone().unwrap_or(preset.pressure),
+            distance: self.distance_unit.clone().unwrap_or(preset.distance),
+            precipitation: self.precip_unit.clone().unwrap_or(preset.precipitation),
+        }
+    }
+
+    /// Identifies the requested location independently of any network lookup.
+    pub fn location_key(&self) -> String {
+        match (&self.zip, self.lat, self.lon) {
+            (Some(zip), _, _) => format!("zip:{}", zip),
+            (None, Some(lat), Some(lon)) => format!("point:{:.4},{:.4}", lat, lon),
+            _ => String::new(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum TemperatureUnit {
     Fahrenheit,
     Celsius,
 }
 
+#[derive(Clone, Debug)]
+pub enum UnitSystem {
+    Imperial,
+    Metric,
+    Si,
+    Uk,
+}
+
+#[derive(Clone, Debug)]
+pub enum WindUnit {
+    MilesPerHour,
+    KilometersPerHour,
+    MetersPerSecond,
+    Knots,
+    Beaufort,
+}
+
+#[derive(Clone, Debug)]
+pub enum PressureUnit {
+    InchesOfMercury,
+    Hectopascal,
+    Millibar,
+    MillimetersOfMercury,
+}
+
+#[derive(Clone, Debug)]
+pub enum DistanceUnit {
+    Miles,
+    Kilometers,
+}
+
+#[derive(Clone, Debug)]
+pub enum PrecipUnit {
+    Inches,
+    Millimeters,
+}
+
+#[derive(Clone, Debug)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub wind: WindUnit,
+    pub pressure: PressureUnit,
+    pub distance: DistanceUnit,
+    pub precipitation: PrecipUnit,
+}
+
+impl Units {
+    pub fn for_system(system: &UnitSystem) -> Self {
+        match system {
+            UnitSystem::Imperial => Units {
+                temperature: TemperatureUnit::Fahrenheit,
+                wind: WindUnit::MilesPerHour,
+                pressure: PressureUnit::InchesOfMercury,
+                distance: DistanceUnit::Miles,
+                precipitation: PrecipUnit::Inches,
+            },
+            UnitSystem::Metric => Units {
+                temperature: TemperatureUnit::Celsius,
+                wind: WindUnit::KilometersPerHour,
+                pressure: PressureUnit::Hectopascal,
+                distance: DistanceUnit::Kilometers,
+                precipitation: PrecipUnit::Millimeters,
+            },
+            UnitSystem::Si => Units {
+                temperature: TemperatureUnit::Celsius,
+                wind: WindUnit::MetersPerSecond,
+                pressure: PressureUnit::Hectopascal,
+                distance: DistanceUnit::Kilometers,
+                precipitation: PrecipUnit::Millimeters,
+            },
+            // Road signs in miles, forecasts in Celsius and millibars
+            UnitSystem::Uk => Units {
+                temperature: TemperatureUnit::Celsius,
+                wind: WindUnit::MilesPerHour,
+                pressure: PressureUnit::Millibar,
+                distance: DistanceUnit::Miles,
+                precipitation: PrecipUnit::Millimeters,
+            },
+        }
+    }
+}
+
+impl TemperatureUnit {
+    pub fn unit(&self) -> Unit {
+        match self {
+            TemperatureUnit::Fahrenheit => Unit::Fahrenheit,
+            TemperatureUnit::Celsius => Unit::Celsius,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        self.unit().symbol()
+    }
+}
+
+impl WindUnit {
+    /// The linear unit to convert to; Beaufort is a scale, not a unit.
+    pub fn unit(&self) -> Option<Unit> {
+        match self {
+            WindUnit::MilesPerHour => Some(Unit::MilesPerHour),
+            WindUnit::KilometersPerHour => Some(Unit::KilometersPerHour),
+            WindUnit::MetersPerSecond => Some(Unit::MetersPerSecond),
+            WindUnit::Knots => Some(Unit::Knots),
+            WindUnit::Beaufort => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            WindUnit::Beaufort => "Bft",
+            other => other.unit().map(Unit::symbol).unwrap_or_default(),
+        }
+    }
+}
+
+impl PressureUnit {
+    pub fn unit(&self) -> Unit {
+        match self {
+            PressureUnit::InchesOfMercury => Unit::InchesOfMercury,
+            PressureUnit::Hectopascal | PressureUnit::Millibar => Unit::Hectopascal,
+            PressureUnit::MillimetersOfMercury => Unit::MillimetersOfMercury,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PressureUnit::Millibar => "mb",
+            other => other.unit().symbol(),
+        }
+    }
+
+    pub fn decimals(&self) -> usize {
+        match self {
+            PressureUnit::InchesOfMercury => 2,
+            _ => 0,
+        }
+    }
+}
+
+impl DistanceUnit {
+    pub fn unit(&self) -> Unit {
+        match self {
+            DistanceUnit::Miles => Unit::Mile,
+            DistanceUnit::Kilometers => Unit::Kilometer,
+        }
+    }
+
+    /// Unit for short distances such as cloud heights.
+    pub fn short_unit(&self) -> Unit {
+        match self {
+            DistanceUnit::Miles => Unit::Foot,
+            DistanceUnit::Kilometers => Unit::Meter,
+        }
+    }
+}
+
+impl PrecipUnit {
+    pub fn unit(&self) -> Unit {
+        match self {
+            PrecipUnit::Inches => Unit::Inch,
+            PrecipUnit::Millimeters => Unit::Millimeter,
+        }
+    }
+
+    pub fn decimals(&self) -> usize {
+        match self {
+            PrecipUnit::Inches => 2,
+            PrecipUnit::Millimeters => 1,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum IconSet {
     Unicode,
@@ -57,7 +418,7 @@ pub enum OutputFormat {
     Json,
 }
 
-fn parse_unit(s: &str) -> Result<TemperatureUnit, String> {
+pub(crate) fn parse_unit(s: &str) -> Result<TemperatureUnit, String> {
     match s.to_uppercase().as_str() {
         "F" | "FAHRENHEIT" => Ok(TemperatureUnit::Fahrenheit),
         "C" | "CELSIUS" => Ok(TemperatureUnit::Celsius),
@@ -65,7 +426,77 @@ fn parse_unit(s: &str) -> Result<TemperatureUnit, String> {
     }
 }
 
-fn parse_icon_set(s: &str) -> Result<IconSet, String> {
+pub(crate) fn parse_precision(s: &str) -> Result<usize, String> {
+    match s.trim() {
+        "0" => Ok(0),
+        "1" => Ok(1),
+        _ => Err(format!("Invalid precision: {}. Use 0 or 1", s)),
+    }
+}
+
+pub(crate) fn parse_unit_system(s: &str) -> Result<UnitSystem, String> {
+    match s.to_lowercase().as_str() {
+        "imperial" | "us" => Ok(UnitSystem::Imperial),
+        "metric" => Ok(UnitSystem::Metric),
+        "si" => Ok(UnitSystem::Si),
+        "uk" => Ok(UnitSystem::Uk),
+        _ => Err(format!("Invalid unit system: {}. Use imperial, metric, si, or uk", s)),
+    }
+}
+
+pub(crate) fn parse_wind_unit(s: &str) -> Result<WindUnit, String> {
+    match s.to_lowercase().as_str() {
+        "mph" => Ok(WindUnit::MilesPerHour),
+        "kmh" | "km/h" | "kph" => Ok(WindUnit::KilometersPerHour),
+        "ms" | "m/s" => Ok(WindUnit::MetersPerSecond),
+        "kt" | "kts" | "knots" => Ok(WindUnit::Knots),
+        "bft" | "beaufort" => Ok(WindUnit::Beaufort),
+        _ => Err(format!("Invalid wind unit: {}. Use mph, kmh, ms, kt, or bft", s)),
+    }
+}
+
+pub(crate) fn parse_pressure_unit(s: &str) -> Result<PressureUnit, String> {
+    match s.to_lowercase().as_str() {
+        "inhg" => Ok(PressureUnit::InchesOfMercury),
+        "hpa" => Ok(PressureUnit::Hectopascal),
+        "mb" | "mbar" => Ok(PressureUnit::Millibar),
+        "mmhg" => Ok(PressureUnit::MillimetersOfMercury),
+        _ => Err(format!("Invalid pressure unit: {}. Use inHg, hPa, mb, or mmHg", s)),
+    }
+}
+
+pub(crate) fn parse_distance_unit(s: &str) -> Result<DistanceUnit, String> {
+    match s.to_lowercase().as_str() {
+        "mi" | "miles" => Ok(DistanceUnit::Miles),
+        "km" | "kilometers" => Ok(DistanceUnit::Kilometers),
+        _ => Err(format!("Invalid distance unit: {}. Use mi or km", s)),
+    }
+}
+
+pub(crate) fn parse_precip_unit(s: &str) -> Result<PrecipUnit, String> {
+    match s.to_lowercase().as_str() {
+        "in" | "inches" => Ok(PrecipUnit::Inches),
+        "mm" => Ok(PrecipUnit::Millimeters),
+        _ => Err(format!("Invalid precipitation unit: {}. Use in or mm", s)),
+    }
+}
+
+/// Parses a depth such as `6in`, `15cm` or `20mm` into millimeters.
+pub(crate) fn parse_depth(s: &str) -> Result<f64, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: f64 = value.parse().map_err(|_| format!("Invalid depth: {}. Use e.g. 6in, 15cm or 20mm", s))?;
+    let unit = match unit.trim().to_lowercase().as_str() {
+        "in" => Unit::Inch,
+        "cm" => Unit::Centimeter,
+        "mm" => Unit::Millimeter,
+        _ => return Err(format!("Invalid depth: {}. Use e.g. 6in, 15cm or 20mm", s)),
+    };
+    Ok(convert(value, unit, Unit::Millimeter))
+}
+
+pub(crate) fn parse_icon_set(s: &str) -> Result<IconSet, String> {
     match s.to_lowercase().as_str() {
         "unicode" => Ok(IconSet::Unicode),
         "emoji" => Ok(IconSet::Emoji),
@@ -75,7 +506,7 @@ fn parse_icon_set(s: &str) -> Result<IconSet, String> {
     }
 }
 
-fn parse_output_format(s: &str) -> Result<OutputFormat, String> {
+pub(crate) fn parse_output_format(s: &str) -> Result<OutputFormat, String> {
     match s.to_lowercase().as_str() {
         "waybar" => Ok(OutputFormat::Waybar),
         "plain" => Ok(OutputFormat::Plain),
@@ -83,3 +514,31 @@ fn parse_output_format(s: &str) -> Result<OutputFormat, String> {
         _ => Err(format!("Invalid format: {}. Use waybar, plain, or json", s)),
     }
 }
+
+/// A daemon refresh interval; anything shorter would hammer the API.
+pub(crate) fn parse_interval(s: &str) -> Result<Duration, String> {
+    let interval = parse_duration(s)?;
+    if interval < MIN_INTERVAL {
+        return Err(format!("Interval too short: {}. Use at least {}s", s, MIN_INTERVAL.as_secs()));
+    }
+    Ok(interval)
+}
+
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split);
+    let value: u64 = number.parse()
+        .map_err(|_| format!("Invalid duration: {}. Use e.g. 30s, 10m, 2h or 1d", s))?;
+
+    let scale = match suffix {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("Invalid duration: {}. Use e.g. 30s, 10m, 2h or 1d", s)),
+    };
+    let seconds = value.checked_mul(scale)
+        .ok_or_else(|| format!("Invalid duration: {}. Use e.g. 30s, 10m, 2h or 1d", s))?;
+    Ok(Duration::from_secs(seconds))
+}
@@ -0,0 +1,193 @@
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{
+    parse_depth, parse_distance_unit, parse_duration, parse_icon_set, parse_interval, parse_output_format,
+    parse_precip_unit, parse_precision, parse_pressure_unit, parse_unit, parse_unit_system, parse_wind_unit, Args,
+};
+use crate::error::WeatherError;
+
+const CONFIG_DIR_NAME: &str = "nws-weather";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Contents of `config.toml`. Every key is optional; command-line flags take
+/// precedence over anything set here.
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub unit: Option<String>,
+    pub unit_system: Option<String>,
+    pub precision: Option<usize>,
+    pub wind_unit: Option<String>,
+    pub pressure_unit: Option<String>,
+    pub distance_unit: Option<String>,
+    pub precip_unit: Option<String>,
+    pub icons: Option<String>,
+    pub format: Option<String>,
+    pub detailed: Option<bool>,
+    pub wait_for_network: Option<bool>,
+    pub trend: Option<bool>,
+    pub heavy_snow: Option<String>,
+    pub heavy_ice: Option<String>,
+    pub heavy_rain: Option<String>,
+    pub max_stale: Option<String>,
+    pub interval: Option<String>,
+    pub text_template: Option<String>,
+    pub tooltip_template: Option<String>,
+    pub station: Option<String>,
+    pub max_observation_age: Option<String>,
+    /// Name of the entry in `locations` used when none is given on the command line
+    pub location: Option<String>,
+    #[serde(default)]
+    pub locations: BTreeMap<String, LocationEntry>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct LocationEntry {
+    pub zip: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+}
+
+impl ConfigFile {
+    /// `$XDG_CONFIG_HOME/nws-weather/config.toml`, falling back to `$HOME/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, WeatherError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| WeatherError::Config(format!("{}: {}", path.display(), e)))?;
+        toml::from_str(&contents)
+            .map_err(|e| WeatherError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Loads the file named by `--config`, or the default file if it exists.
+    pub fn discover(args: &Args) -> Result<Self, WeatherError> {
+        match &args.config {
+            Some(path) => Self::load(path),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => Self::load(&path),
+                _ => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// Fills in every setting that wasn't given explicitly on the command line.
+    pub fn apply(&self, args: &mut Args, matches: &ArgMatches) -> Result<(), WeatherError> {
+        let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        let invalid = |key: &str, e: String| WeatherError::Config(format!("{}: {}", key, e));
+
+        // A preset picked on the command line replaces the file's whole unit
+        // setup, so only command-line overrides apply on top of it
+        let system_from_cli = from_cli("unit_system");
+        let unit_from_cli = |id: &str| system_from_cli || from_cli(id);
+
+        if let (Some(unit), false) = (&self.unit, unit_from_cli("unit")) {
+            args.unit = Some(parse_unit(unit).map_err(|e| invalid("unit", e))?);
+        }
+        if let (Some(precision), false) = (self.precision, from_cli("precision")) {
+            args.precision = parse_precision(&precision.to_string()).map_err(|e| invalid("precision", e))?;
+        }
+        if let (Some(system), false) = (&self.unit_system, system_from_cli) {
+            args.unit_system = Some(parse_unit_system(system).map_err(|e| invalid("unit_system", e))?);
+        }
+        if let (Some(unit), false) = (&self.wind_unit, unit_from_cli("wind_unit")) {
+            args.wind_unit = Some(parse_wind_unit(unit).map_err(|e| invalid("wind_unit", e))?);
+        }
+        if let (Some(unit), false) = (&self.pressure_unit, unit_from_cli("pressure_unit")) {
+            args.pressure_unit = Some(parse_pressure_unit(unit).map_err(|e| invalid("pressure_unit", e))?);
+        }
+        if let (Some(unit), false) = (&self.distance_unit, unit_from_cli("distance_unit")) {
+            args.distance_unit = Some(parse_distance_unit(unit).map_err(|e| invalid("distance_unit", e))?);
+        }
+        if let (Some(unit), false) = (&self.precip_unit, unit_from_cli("precip_unit")) {
+            args.precip_unit = Some(parse_precip_unit(unit).map_err(|e| invalid("precip_unit", e))?);
+        }
+        if let (Some(icons), false) = (&self.icons, from_cli("icons")) {
+            args.icons = parse_icon_set(icons).map_err(|e| invalid("icons", e))?;
+        }
+        if let (Some(format), false) = (&self.format, from_cli("format")) {
+            args.format = parse_output_format(format).map_err(|e| invalid("format", e))?;
+        }
+        if let (Some(detailed), false) = (self.detailed, from_cli("detailed")) {
+            args.detailed = detailed;
+        }
+        if let (Some(wait), false) = (self.wait_for_network, from_cli("wait_for_network")) {
+            args.wait_for_network = wait;
+        }
+        if let (Some(trend), false) = (self.trend, from_cli("trend")) {
+            args.trend = trend;
+        }
+        if let (Some(depth), false) = (&self.heavy_snow, from_cli("heavy_snow")) {
+            args.heavy_snow = parse_depth(depth).map_err(|e| invalid("heavy_snow", e))?;
+        }
+        if let (Some(depth), false) = (&self.heavy_ice, from_cli("heavy_ice")) {
+            args.heavy_ice = parse_depth(depth).map_err(|e| invalid("heavy_ice", e))?;
+        }
+        if let (Some(depth), false) = (&self.heavy_rain, from_cli("heavy_rain")) {
+            args.heavy_rain = parse_depth(depth).map_err(|e| invalid("heavy_rain", e))?;
+        }
+        if let (Some(max_stale), false) = (&self.max_stale, from_cli("max_stale")) {
+            args.max_stale = parse_duration(max_stale).map_err(|e| invalid("max_stale", e))?;
+        }
+        if let (Some(interval), false) = (&self.interval, from_cli("interval")) {
+            args.interval = parse_interval(interval).map_err(|e| invalid("interval", e))?;
+        }
+        if let (Some(station), false) = (&self.station, from_cli("station")) {
+            args.station = Some(station.clone());
+        }
+        if let (Some(age), false) = (&self.max_observation_age, from_cli("max_observation_age")) {
+            args.max_observation_age = parse_duration(age).map_err(|e| invalid("max_observation_age", e))?;
+        }
+        if let (Some(template), false) = (&self.text_template, from_cli("text_template")) {
+            args.text_template = Some(template.clone());
+        }
+        if let (Some(template), false) = (&self.tooltip_template, from_cli("tooltip_template")) {
+            args.tooltip_template = Some(template.clone());
+        }
+
+        // Explicit coordinates or ZIP on the command line win over named locations
+        if args.zip.is_some() || args.lat.is_some() {
+            return Ok(());
+        }
+
+        let Some(name) = args.location.as_ref().or(self.location.as_ref()) else {
+            return Ok(());
+        };
+
+        let entry = self.locations.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.locations.keys().map(String::as_str).collect();
+            WeatherError::Config(format!(
+                "unknown location '{}' (configured: {})",
+                name,
+                if known.is_empty() { "none".to_string() } else { known.join(", ") }
+            ))
+        })?;
+
+        match entry {
+            LocationEntry { zip: Some(zip), lat: None, lon: None } => args.zip = Some(zip.clone()),
+            LocationEntry { zip: None, lat: Some(lat), lon: Some(lon) } => {
+                args.lat = Some(*lat);
+                args.lon = Some(*lon);
+            }
+            _ => {
+                return Err(WeatherError::Config(format!(
+                    "location '{}' must set either zip or both lat and lon",
+                    name
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::client::WeatherClient;
+use crate::config::Args;
+use crate::error::WeatherError;
+use crate::output::create_error_output;
+use crate::{refresh_weather_app, run_weather_app};
+
+// Retry delays after failed refreshes, doubling up to the refresh interval
+const MIN_ERROR_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Refreshes forever, printing one output line per update for Waybar's
+/// continuous `exec` protocol. SIGUSR1 forces an immediate refresh.
+pub async fn run_daemon(client: &WeatherClient, args: &Args) -> Result<(), WeatherError> {
+    let mut refresh_signal = RefreshSignal::new()?;
+    let mut failures: u32 = 0;
+    let mut startup = true;
+
+    loop {
+        // Only the first refresh pays the startup delay / network wait
+        let result = if startup {
+            run_weather_app(client, args).await
+        } else {
+            refresh_weather_app(client, args).await
+        };
+        startup = false;
+
+        let delay = match result {
+            // Stale output means the refresh failed, so retry on the error schedule
+            Ok(app) if app.stale => {
+                failures += 1;
+                println!("{}", app.output);
+                error_backoff(failures, args.interval)
+            }
+            Ok(app) => {
+                failures = 0;
+                println!("{}", app.output);
+                args.interval
+            }
+            Err(e) => {
+                failures += 1;
+                eprintln!("Error: {}", e);
+                if let Some(fallback) = create_error_output(&e, args) {
+                    println!("{}", fallback);
+                }
+                error_backoff(failures, args.interval)
+            }
+        };
+
+        tokio::select! {
+            _ = sleep(delay) => {}
+            _ = refresh_signal.recv() => {}
+        }
+    }
+}
+
+fn error_backoff(failures: u32, interval: Duration) -> Duration {
+    let backoff = MIN_ERROR_BACKOFF.saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)));
+    backoff.min(interval.max(MIN_ERROR_BACKOFF))
+}
+
+struct RefreshSignal {
+    #[cfg(unix)]
+    signal: tokio::signal::unix::Signal,
+}
+
+impl RefreshSignal {
+    #[cfg(unix)]
+    fn new() -> Result<Self, WeatherError> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self { signal: signal(SignalKind::user_defined1())? })
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Result<Self, WeatherError> {
+        Ok(Self {})
+    }
+
+    #[cfg(unix)]
+    async fn recv(&mut self) {
+        self.signal.recv().await;
+    }
+
+    #[cfg(not(unix))]
+    async fn recv(&mut self) {
+        std::future::pending::<()>().await
+    }
+}
@@ -4,6 +4,8 @@ use thiserror::Error;
 pub enum WeatherError {
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("JSON parsing error: {0}")]
     Json(#[from] serde_json::Error),
     #[error("Number parsing error: {0}")]
@@ -16,6 +18,18 @@ pub enum WeatherError {
     NoWeatherData,
     #[error("API error: {0}")]
     Api(String),
+    #[error("Template error: {0}")]
+    Template(String),
+    #[error("Config error: {0}")]
+    Config(String),
+    #[error("Unknown gridpoint layer: {0}")]
+    UnknownLayer(String),
+    #[error("Invalid time: {0}")]
+    InvalidTime(String),
+    #[error("Unknown unit: {0}")]
+    UnknownUnit(String),
+    #[error("Unit mismatch: {0}")]
+    UnitMismatch(String),
     #[error("Invalid coordinates: lat must be between -90 and 90, lon between -180 and 180")]
     InvalidCoordinates,
 }
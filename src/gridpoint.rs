@@ -0,0 +1,353 @@
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::error::WeatherError;
+use crate::units::{Quantity, Unit};
+
+// Layers whose values are totals over their interval rather than a level
+const ACCUMULATION_LAYERS: [&str; 3] = ["quantitativePrecipitation", "snowfallAmount", "iceAccumulation"];
+
+/// Raw forecast grid data from `/gridpoints/{wfo}/{x},{y}`: one time series
+/// per layer (temperature, skyCover, quantitativePrecipitation, ...).
+#[derive(Clone, Debug)]
+pub struct GridpointData {
+    pub update_time: Option<DateTime<Utc>>,
+    pub layers: BTreeMap<String, GridLayer>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GridLayer {
+    /// Unit code such as `wmoUnit:degC`; absent for non-numeric layers
+    #[serde(default)]
+    pub uom: Option<String>,
+    pub values: Vec<GridValue>,
+    /// Whether each value is a total over its interval, e.g. precipitation
+    #[serde(skip)]
+    pub accumulation: bool,
+}
+
+/// One layer value, valid over an interval. Numeric for most layers; an
+/// array of objects for `weather` and `hazards`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct GridValue {
+    #[serde(rename = "validTime")]
+    pub valid_time: ValidTime,
+    pub value: Value,
+}
+
+/// An ISO-8601 interval like `2026-10-16T18:00:00+00:00/PT3H`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidTime {
+    pub start: DateTime<Utc>,
+    pub duration: Duration,
+}
+
+/// Expected totals over the next `hours`, in mm. Precipitation is the
+/// liquid equivalent of everything that falls, snow and ice included.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Accumulation {
+    pub hours: u32,
+    pub snow: Option<f64>,
+    pub ice: Option<f64>,
+    pub precipitation: Option<f64>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct GridSample<T> {
+    pub time: DateTime<Utc>,
+    pub value: T,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct GridpointResponse {
+    properties: BTreeMap<String, Value>,
+}
+
+impl GridpointData {
+    pub(crate) fn from_response(response: GridpointResponse) -> Result<Self, WeatherError> {
+        let update_time = response.properties.get("updateTime")
+            .and_then(Value::as_str)
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc));
+
+        // Layers are the properties shaped like { uom, values: [...] }; the
+        // rest is grid metadata
+        let mut layers = BTreeMap::new();
+        for (name, value) in response.properties {
+            if value.get("values").is_some_and(Value::is_array) {
+                let mut layer: GridLayer = serde_json::from_value(value)?;
+                layer.accumulation = ACCUMULATION_LAYERS.contains(&name.as_str());
+                layers.insert(name, layer);
+            }
+        }
+
+        Ok(Self { update_time, layers })
+    }
+
+    pub fn layer(&self, name: &str) -> Option<&GridLayer> {
+        self.layers.get(name)
+    }
+
+    pub fn layer_names(&self) -> impl Iterator<Item = &str> {
+        self.layers.keys().map(String::as_str)
+    }
+
+    /// Snow, ice and liquid precipitation expected over `hours` from `start`.
+    pub fn accumulation(&self, start: DateTime<Utc>, hours: u32) -> Result<Accumulation, WeatherError> {
+        let end = start + Duration::hours(hours.into());
+        let total = |name: &str| match self.layer(name) {
+            Some(layer) => layer.total_between(start, end, Unit::Millimeter),
+            None => Ok(None),
+        };
+        Ok(Accumulation {
+            hours,
+            snow: total("snowfallAmount")?,
+            ice: total("iceAccumulation")?,
+            precipitation: total("quantitativePrecipitation")?,
+        })
+    }
+}
+
+impl GridLayer {
+    /// The layer's unit; errors if `uom` is set but unrecognized.
+    pub fn unit(&self) -> Result<Option<Unit>, WeatherError> {
+        self.uom.as_deref().map(Unit::from_code).transpose()
+    }
+
+    /// The value in effect at `time`, if any interval covers it.
+    pub fn value_at(&self, time: DateTime<Utc>) -> Option<&Value> {
+        self.values.iter()
+            .find(|value| value.valid_time.contains(time))
+            .map(|value| &value.value)
+    }
+
+    /// Expands every interval into one sample per hour from `start` until
+    /// `end`. Levels such as temperature repeat in every hour; accumulations
+    /// are spread evenly over the hours of their interval.
+    pub fn hourly(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<GridSample<Value>> {
+        let mut samples = Vec::new();
+        for value in &self.values {
+            let mut time = value.valid_time.start;
+            while time < value.valid_time.end() && time < end {
+                if time >= start {
+                    samples.push(GridSample { time, value: self.hour_value(value, time) });
+                }
+                time += Duration::hours(1);
+            }
+        }
+        samples
+    }
+
+    fn hour_value(&self, value: &GridValue, time: DateTime<Utc>) -> Value {
+        match value.value.as_f64() {
+            Some(amount) if self.accumulation && value.valid_time.duration > Duration::zero() => {
+                let hour = (time + Duration::hours(1)).min(value.valid_time.end()) - time;
+                let share = hour.num_seconds() as f64 / value.valid_time.duration.num_seconds() as f64;
+                Value::from(amount * share)
+            }
+            _ => value.value.clone(),
+        }
+    }
+
+    /// Like [`GridLayer::hourly`], with numeric values converted to `unit`.
+    /// Null values are skipped.
+    pub fn hourly_in(&self, unit: Unit, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<GridSample<f64>>, WeatherError> {
+        let from = self.unit()?
+            .ok_or_else(|| WeatherError::UnknownUnit("layer has no uom".to_string()))?;
+        self.hourly(start, end)
+            .into_iter()
+            .filter_map(|sample| sample.value.as_f64().map(|value| (sample.time, value)))
+            .map(|(time, value)| Ok(GridSample { time, value: Quantity::new(value, from).to(unit)? }))
+            .collect()
+    }
+
+    /// Sums an accumulation layer between `start` and `end`, prorating
+    /// intervals that only partly overlap. `None` if no interval overlaps.
+    pub fn total_between(&self, start: DateTime<Utc>, end: DateTime<Utc>, unit: Unit) -> Result<Option<f64>, WeatherError> {
+        let mut total = None;
+        for value in &self.values {
+            let Some(amount) = value.value.as_f64() else {
+                continue;
+            };
+            let overlap = value.valid_time.end().min(end) - value.valid_time.start.max(start);
+            if overlap <= Duration::zero() || value.valid_time.duration <= Duration::zero() {
+                continue;
+            }
+            let fraction = overlap.num_seconds() as f64 / value.valid_time.duration.num_seconds() as f64;
+            *total.get_or_insert(0.0) += amount * fraction;
+        }
+
+        match (total, self.unit()?) {
+            (Some(total), Some(from)) => Ok(Some(Quantity::new(total, from).to(unit)?)),
+            (Some(_), None) => Err(WeatherError::UnknownUnit("layer has no uom".to_string())),
+            (None, _) => Ok(None),
+        }
+    }
+}
+
+impl ValidTime {
+    pub fn end(&self) -> DateTime<Utc> {
+        self.start + self.duration
+    }
+
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        self.start <= time && time < self.end()
+    }
+
+    pub fn parse(interval: &str) -> Result<Self, WeatherError> {
+        let invalid = || WeatherError::InvalidTime(interval.to_string());
+        let (start, duration) = interval.split_once('/').ok_or_else(invalid)?;
+        let start = DateTime::parse_from_rfc3339(start).map_err(|_| invalid())?.with_timezone(&Utc);
+        let duration = parse_iso_duration(duration).ok_or_else(invalid)?;
+        Ok(Self { start, duration })
+    }
+}
+
+impl<'de> Deserialize<'de> for ValidTime {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let interval = String::deserialize(deserializer)?;
+        ValidTime::parse(&interval).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for ValidTime {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}/{}", self.start.to_rfc3339(), format_iso_duration(self.duration)))
+    }
+}
+
+/// Parses the duration forms the API uses: `PT1H`, `P1D`, `P1DT6H`, `PT30M`.
+fn parse_iso_duration(s: &str) -> Option<Duration> {
+    let rest = s.strip_prefix('P')?;
+    let (date, time) = match rest.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (rest, None),
+    };
+
+    let mut total = Duration::zero();
+    let mut parse_part = |part: &str, units: &[(char, Duration)]| -> Option<()> {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() {
+                number.push(c);
+                continue;
+            }
+            let (_, unit) = units.iter().find(|(designator, _)| *designator == c)?;
+            total += *unit * number.parse::<i32>().ok()?;
+            number.clear();
+        }
+        number.is_empty().then_some(())
+    };
+
+    parse_part(date, &[('W', Duration::weeks(1)), ('D', Duration::days(1))])?;
+    if let Some(time) = time {
+        parse_part(time, &[('H', Duration::hours(1)), ('M', Duration::minutes(1)), ('S', Duration::seconds(1))])?;
+    }
+    Some(total)
+}
+
+fn format_iso_duration(duration: Duration) -> String {
+    let seconds = duration.num_seconds();
+    let (days, hours, minutes, seconds) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60, seconds % 60);
+
+    let mut text = "P".to_string();
+    if days > 0 {
+        text.push_str(&format!("{}D", days));
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || days == 0 {
+        text.push('T');
+        if hours > 0 {
+            text.push_str(&format!("{}H", hours));
+        }
+        if minutes > 0 {
+            text.push_str(&format!("{}M", minutes));
+        }
+        if seconds > 0 || (hours == 0 && minutes == 0) {
+            text.push_str(&format!("{}S", seconds));
+        }
+    }
+    text
+}
+
+/// The start of the current hour, where hourly listings begin.
+pub fn current_hour() -> DateTime<Utc> {
+    let now = Utc::now();
+    now.duration_trunc(Duration::hours(1)).unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn layer(uom: &str, values: &[(&str, f64)]) -> GridLayer {
+        let values: Vec<Value> = values.iter()
+            .map(|(valid_time, value)| serde_json::json!({ "validTime": valid_time, "value": value }))
+            .collect();
+        serde_json::from_value(serde_json::json!({ "uom": uom, "values": values })).unwrap()
+    }
+
+    #[test]
+    fn parses_iso_durations() {
+        assert_eq!(parse_iso_duration("PT1H"), Some(Duration::hours(1)));
+        assert_eq!(parse_iso_duration("P1D"), Some(Duration::days(1)));
+        assert_eq!(parse_iso_duration("P1DT6H"), Some(Duration::hours(30)));
+        assert_eq!(parse_iso_duration("PT30M"), Some(Duration::minutes(30)));
+        assert_eq!(parse_iso_duration("P1W"), Some(Duration::weeks(1)));
+        assert_eq!(parse_iso_duration("1H"), None);
+        assert_eq!(parse_iso_duration("PT6"), None);
+        assert_eq!(parse_iso_duration("P1H"), None);
+    }
+
+    #[test]
+    fn formats_iso_durations() {
+        assert_eq!(format_iso_duration(Duration::hours(30)), "P1DT6H");
+        assert_eq!(format_iso_duration(Duration::days(1)), "P1D");
+        assert_eq!(format_iso_duration(Duration::zero()), "PT0S");
+    }
+
+    #[test]
+    fn parses_valid_times() {
+        let valid_time = ValidTime::parse("2026-10-16T18:00:00+00:00/P1DT6H").unwrap();
+        assert_eq!(valid_time.start, time("2026-10-16T18:00:00Z"));
+        assert_eq!(valid_time.end(), time("2026-10-18T00:00:00Z"));
+        assert!(valid_time.contains(time("2026-10-16T18:00:00Z")));
+        assert!(!valid_time.contains(valid_time.end()));
+
+        assert!(ValidTime::parse("2026-10-16T18:00:00+00:00").is_err());
+        assert!(ValidTime::parse("yesterday/PT1H").is_err());
+    }
+
+    #[test]
+    fn totals_prorate_partial_overlaps() {
+        let layer = layer("wmoUnit:mm", &[
+            ("2026-10-16T00:00:00+00:00/PT6H", 6.0),
+            ("2026-10-16T06:00:00+00:00/PT6H", 12.0),
+        ]);
+
+        let total = |start, end| layer.total_between(time(start), time(end), Unit::Millimeter).unwrap();
+        assert_eq!(total("2026-10-16T00:00:00Z", "2026-10-16T12:00:00Z"), Some(18.0));
+        assert_eq!(total("2026-10-16T03:00:00Z", "2026-10-16T09:00:00Z"), Some(9.0));
+        assert_eq!(total("2026-10-16T12:00:00Z", "2026-10-17T00:00:00Z"), None);
+
+        let inches = layer.total_between(time("2026-10-16T00:00:00Z"), time("2026-10-16T06:00:00Z"), Unit::Inch).unwrap();
+        assert!((inches.unwrap() - 6.0 / 25.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn hourly_spreads_accumulations_and_repeats_levels() {
+        let mut precipitation = layer("wmoUnit:mm", &[("2026-10-16T00:00:00+00:00/PT3H", 6.0)]);
+        precipitation.accumulation = true;
+        let temperature = layer("wmoUnit:degC", &[("2026-10-16T00:00:00+00:00/PT3H", 20.0)]);
+
+        let (start, end) = (time("2026-10-16T00:00:00Z"), time("2026-10-17T00:00:00Z"));
+        let values = |layer: &GridLayer| layer.hourly(start, end).into_iter().map(|sample| sample.value).collect::<Vec<_>>();
+        assert_eq!(values(&precipitation), vec![Value::from(2.0); 3]);
+        assert_eq!(values(&temperature), vec![Value::from(20.0); 3]);
+    }
+}
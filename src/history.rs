@@ -0,0 +1,170 @@
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::Station;
+
+// Changes smaller than these over the trend window count as steady
+const TEMPERATURE_TREND_THRESHOLD: f64 = 1.0; // °C
+const PRESSURE_TREND_THRESHOLD: f64 = 100.0; // Pa, i.e. 1 hPa
+
+// Barometric tendency is conventionally reported over three hours
+const TREND_WINDOW_HOURS: i64 = 3;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+impl Trend {
+    fn from_change(change: f64, threshold: f64) -> Self {
+        if change >= threshold {
+            Trend::Rising
+        } else if change <= -threshold {
+            Trend::Falling
+        } else {
+            Trend::Steady
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Trend::Rising => "rising",
+            Trend::Falling => "falling",
+            Trend::Steady => "steady",
+        }
+    }
+}
+
+/// One station reading, in °C and Pa.
+#[derive(Serialize, Clone, Debug)]
+pub struct HistoryPoint {
+    pub time: DateTime<Utc>,
+    pub temperature: Option<f64>,
+    pub pressure: Option<f64>,
+}
+
+/// A raw station reading, in °C and Pa, before a pressure kind is chosen.
+#[derive(Clone, Debug)]
+pub struct Reading {
+    pub time: DateTime<Utc>,
+    pub temperature: Option<f64>,
+    pub sea_level_pressure: Option<f64>,
+    pub station_pressure: Option<f64>,
+}
+
+/// Recent observations from one station, oldest first, with derived stats.
+#[derive(Serialize, Clone, Debug)]
+pub struct History {
+    pub station: Station,
+    pub points: Vec<HistoryPoint>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub temperature_trend: Option<Trend>,
+    pub pressure_trend: Option<Trend>,
+}
+
+impl History {
+    pub fn new(station: Station, mut points: Vec<HistoryPoint>) -> Self {
+        points.sort_by_key(|point| point.time);
+
+        let temperatures = || points.iter().filter_map(|point| point.temperature);
+        let high = temperatures().reduce(f64::max);
+        let low = temperatures().reduce(f64::min);
+        let temperature_trend = trend(&points, |point| point.temperature, TEMPERATURE_TREND_THRESHOLD);
+        let pressure_trend = trend(&points, |point| point.pressure, PRESSURE_TREND_THRESHOLD);
+
+        Self { station, points, high, low, temperature_trend, pressure_trend }
+    }
+
+    /// Builds the history with one pressure kind for the whole series: sea
+    /// level if any reading has it, station pressure otherwise. The two
+    /// differ by far more than a trend at elevated stations, so mixing them
+    /// would show false trends.
+    pub fn from_readings(station: Station, readings: Vec<Reading>) -> Self {
+        let sea_level = readings.iter().any(|reading| reading.sea_level_pressure.is_some());
+        let points = readings.into_iter()
+            .map(|reading| HistoryPoint {
+                time: reading.time,
+                temperature: reading.temperature,
+                pressure: if sea_level { reading.sea_level_pressure } else { reading.station_pressure },
+            })
+            .collect();
+        Self::new(station, points)
+    }
+
+    pub fn latest_temperature(&self) -> Option<f64> {
+        self.points.iter().rev().find_map(|point| point.temperature)
+    }
+
+    pub fn latest_pressure(&self) -> Option<f64> {
+        self.points.iter().rev().find_map(|point| point.pressure)
+    }
+
+    /// The last reading in each clock hour, for compact listings.
+    pub fn hourly(&self) -> Vec<&HistoryPoint> {
+        let mut hourly: Vec<&HistoryPoint> = Vec::new();
+        for point in &self.points {
+            match hourly.last_mut() {
+                Some(last) if clock_hour(last.time) == clock_hour(point.time) => *last = point,
+                _ => hourly.push(point),
+            }
+        }
+        hourly
+    }
+}
+
+/// Compares the latest value with the newest one at least a full trend window
+/// older. `None` when the history doesn't reach back that far.
+fn trend(points: &[HistoryPoint], value: fn(&HistoryPoint) -> Option<f64>, threshold: f64) -> Option<Trend> {
+    let (latest_time, latest) = points.iter().rev().find_map(|point| value(point).map(|v| (point.time, v)))?;
+    let cutoff = latest_time - chrono::Duration::hours(TREND_WINDOW_HOURS);
+    let earlier = points.iter().rev().filter(|point| point.time <= cutoff).find_map(value)?;
+    Some(Trend::from_change(latest - earlier, threshold))
+}
+
+fn clock_hour(time: DateTime<Utc>) -> (NaiveDate, u32) {
+    (time.date_naive(), time.hour())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn station() -> Station {
+        Station { id: "KXYZ".to_string(), name: String::new(), distance_km: None }
+    }
+
+    fn reading(hour: i64, sea_level_pressure: Option<f64>, station_pressure: Option<f64>) -> Reading {
+        Reading {
+            time: DateTime::parse_from_rfc3339("2026-10-16T00:00:00Z").unwrap().with_timezone(&Utc) + chrono::Duration::hours(hour),
+            temperature: Some(10.0),
+            sea_level_pressure,
+            station_pressure,
+        }
+    }
+
+    #[test]
+    fn mixed_pressure_series_uses_one_kind() {
+        // The latest report has no sea-level pressure, as special reports often don't
+        let history = History::from_readings(station(), vec![
+            reading(0, Some(101_300.0), Some(85_000.0)),
+            reading(3, Some(101_320.0), Some(85_010.0)),
+            reading(4, None, Some(85_020.0)),
+        ]);
+        assert_eq!(history.latest_pressure(), Some(101_320.0));
+        assert_eq!(history.pressure_trend, Some(Trend::Steady));
+    }
+
+    #[test]
+    fn station_pressure_when_no_sea_level_readings() {
+        let history = History::from_readings(station(), vec![
+            reading(0, None, Some(85_000.0)),
+            reading(3, None, Some(84_800.0)),
+        ]);
+        assert_eq!(history.latest_pressure(), Some(84_800.0));
+        assert_eq!(history.pressure_trend, Some(Trend::Falling));
+    }
+}
@@ -1,112 +1,180 @@
+use crate::condition::WeatherCondition;
 use crate::config::IconSet;
+use crate::history::Trend;
+use crate::wind;
 
 pub fn get_weather_icon(condition: &str, icon_set: &IconSet) -> &'static str {
-    let condition_lower = condition.to_lowercase();
-    
+    get_condition_icon(WeatherCondition::from_text(condition), true, icon_set)
+}
+
+pub fn get_condition_icon(condition: WeatherCondition, is_day: bool, icon_set: &IconSet) -> &'static str {
+    if is_day {
+        get_day_icon(condition, icon_set)
+    } else {
+        get_night_icon(condition, icon_set)
+    }
+}
+
+fn get_night_icon(condition: WeatherCondition, icon_set: &IconSet) -> &'static str {
+    use WeatherCondition::*;
+
+    // Conditions without a night variant fall back to the day glyph
+    match (icon_set, condition) {
+        (IconSet::NerdFont, Clear) => "󰖔", // nf-md-weather_night
+        (IconSet::NerdFont, FewClouds | PartlyCloudy) => "󰼱", // nf-md-weather_night_partly_cloudy
+        (IconSet::NerdFont, MostlyCloudy) => "", // nf-weather-night_alt_cloudy
+        (IconSet::NerdFont, Rain) => "", // nf-weather-night_rain
+        (IconSet::NerdFont, Showers) => "", // nf-weather-night_showers
+        (IconSet::NerdFont, Thunderstorm) => "", // nf-weather-night_thunderstorm
+        (IconSet::NerdFont, Snow) => "", // nf-weather-night_snow
+        (IconSet::NerdFont, Fog) => "", // nf-weather-night_fog
+        (IconSet::Unicode, Clear) => "☾",
+        (IconSet::Unicode, FewClouds | PartlyCloudy | MostlyCloudy) => "☁",
+        (IconSet::Unicode, Showers) => "🌧",
+        (IconSet::Emoji, Clear) => "🌙",
+        (IconSet::Emoji, FewClouds | PartlyCloudy | MostlyCloudy) => "☁️",
+        (IconSet::Emoji, Showers) => "🌧️",
+        (IconSet::Text, Clear) => "CLR",
+        (IconSet::Text, FewClouds) => "M.CLR",
+        _ => get_day_icon(condition, icon_set),
+    }
+}
+
+fn get_day_icon(condition: WeatherCondition, icon_set: &IconSet) -> &'static str {
+    use WeatherCondition::*;
+
+    match icon_set {
+        IconSet::NerdFont => match condition {
+            Clear => "󰖙", // nf-md-weather_sunny
+            FewClouds | PartlyCloudy => "󰖕", // nf-md-weather_partly_cloudy
+            MostlyCloudy | Overcast => "󰖐", // nf-md-weather_cloudy
+            Rain => "󰖗", // nf-md-weather_rainy
+            Showers => "󰼳", // nf-md-weather_partly_rainy
+            Thunderstorm => "󰖓", // nf-md-weather_lightning
+            Snow => "󰖘", // nf-md-weather_snowy
+            Sleet => "󰖒", // nf-md-weather_hail
+            FreezingRain => "󰙿", // nf-md-weather_snowy_rainy
+            Blizzard => "󰼶", // nf-md-weather_snowy_heavy
+            Fog => "󰖑", // nf-md-weather_fog
+            Haze | Smoke | Dust => "󰼰", // nf-md-weather_hazy
+            Wind => "󰖝", // nf-md-weather_windy
+            Hot => "󰔐", // nf-md-thermometer_lines
+            Cold => "󰔒", // nf-md-thermometer_minus
+            Tornado => "󰼸", // nf-md-weather_tornado
+            TropicalStorm | Hurricane => "󰢘", // nf-md-weather_hurricane
+            Unknown => "󰖚", // nf-md-weather_sunset
+        },
+        IconSet::Unicode => match condition {
+            Clear => "☀",
+            FewClouds => "🌤",
+            PartlyCloudy => "⛅",
+            MostlyCloudy => "🌥",
+            Overcast => "☁",
+            Rain => "🌧",
+            Showers => "🌦",
+            Thunderstorm => "⛈",
+            Snow | Cold => "❄",
+            Sleet | FreezingRain | Blizzard => "🌨",
+            Fog | Haze | Smoke | Dust => "🌫",
+            Wind => "💨",
+            Tornado => "🌪",
+            TropicalStorm | Hurricane => "🌀",
+            Hot | Unknown => "🌡",
+        },
+        IconSet::Emoji => match condition {
+            Clear => "☀️",
+            FewClouds => "🌤️",
+            PartlyCloudy => "⛅",
+            MostlyCloudy => "🌥️",
+            Overcast => "☁️",
+            Rain => "🌧️",
+            Showers => "🌦️",
+            Thunderstorm => "⛈️",
+            Snow => "❄️",
+            Sleet | FreezingRain | Blizzard => "🌨️",
+            Fog | Haze | Smoke | Dust => "🌫️",
+            Wind => "💨",
+            Hot => "🥵",
+            Cold => "🥶",
+            Tornado => "🌪️",
+            TropicalStorm | Hurricane => "🌀",
+            Unknown => "🌡️",
+        },
+        IconSet::Text => match condition {
+            Clear => "SUN",
+            FewClouds => "M.SUN",
+            PartlyCloudy => "P.CLY",
+            MostlyCloudy => "M.CLY",
+            Overcast => "CLDY",
+            Rain => "RAIN",
+            Showers => "SHWR",
+            Thunderstorm => "THRM",
+            Snow => "SNOW",
+            Sleet => "SLEET",
+            FreezingRain => "FZRA",
+            Blizzard => "BLZD",
+            Fog => "FOG",
+            Haze => "HAZE",
+            Smoke => "SMOKE",
+            Dust => "DUST",
+            Wind => "WIND",
+            Hot => "HOT",
+            Cold => "COLD",
+            Tornado => "TORN",
+            TropicalStorm => "TROP",
+            Hurricane => "HURR",
+            Unknown => "WX",
+        },
+    }
+}
+
+pub fn get_alert_icon(icon_set: &IconSet) -> &'static str {
     match icon_set {
-        IconSet::NerdFont => {
-            if condition_lower.contains("sunny") || condition_lower.contains("clear") {
-                "󰖙" // nf-weather-day_sunny
-            } else if condition_lower.contains("partly") {
-                "󰖕" // nf-weather-day_cloudy
-            } else if condition_lower.contains("mostly sunny") {
-                "󰖐" // nf-weather-day_sunny_overcast
-            } else if condition_lower.contains("cloud") || condition_lower.contains("overcast") {
-                "󰖐" // nf-weather-cloudy
-            } else if condition_lower.contains("thunder") || condition_lower.contains("storm") {
-                "󰖓" // nf-weather-thunderstorm
-            } else if condition_lower.contains("rain") || condition_lower.contains("showers") {
-                if condition_lower.contains("light") {
-                    "󰖗" // nf-weather-sprinkle
-                } else {
-                    "󰖖" // nf-weather-rain
-                }
-            } else if condition_lower.contains("snow") {
-                if condition_lower.contains("light") {
-                    "󰖘" // nf-weather-snow
-                } else {
-                    "󰼶" // nf-weather-snow_heavy
-                }
-            } else if condition_lower.contains("fog") || condition_lower.contains("mist") {
-                "󰖑" // nf-weather-fog
-            } else if condition_lower.contains("wind") {
-                "󰖝" // nf-weather-strong_wind
-            } else if condition_lower.contains("hot") {
-                "󰔐" // nf-weather-hot
-            } else if condition_lower.contains("cold") {
-                "󰔒" // nf-weather-snowflake_cold
-            } else {
-                "󰖚" // nf-weather-na (not available)
-            }
-        }
-        IconSet::Unicode => {
-            if condition_lower.contains("sunny") || condition_lower.contains("clear") {
-                "☀"
-            } else if condition_lower.contains("partly") {
-                "⛅"
-            } else if condition_lower.contains("mostly sunny") {
-                "🌤"
-            } else if condition_lower.contains("cloud") {
-                "☁"
-            } else if condition_lower.contains("thunder") {
-                "⛈"
-            } else if condition_lower.contains("rain") || condition_lower.contains("showers") {
-                "🌧"
-            } else if condition_lower.contains("snow") {
-                "❄"
-            } else if condition_lower.contains("fog") || condition_lower.contains("mist") {
-                "🌫"
-            } else if condition_lower.contains("wind") {
-                "💨"
-            } else {
-                "🌡"
-            }
-        }
-        IconSet::Emoji => {
-            if condition_lower.contains("sunny") || condition_lower.contains("clear") {
-                "☀️"
-            } else if condition_lower.contains("partly") {
-                "⛅"
-            } else if condition_lower.contains("mostly sunny") {
-                "🌤️"
-            } else if condition_lower.contains("cloud") {
-                "☁️"
-            } else if condition_lower.contains("thunder") {
-                "⛈️"
-            } else if condition_lower.contains("rain") || condition_lower.contains("showers") {
-                "🌧️"
-            } else if condition_lower.contains("snow") {
-                "❄️"
-            } else if condition_lower.contains("fog") || condition_lower.contains("mist") {
-                "🌫️"
-            } else if condition_lower.contains("wind") {
-                "💨"
-            } else {
-                "🌡️"
-            }
-        }
-        IconSet::Text => {
-            if condition_lower.contains("sunny") || condition_lower.contains("clear") {
-                "SUN"
-            } else if condition_lower.contains("partly") {
-                "P.CLY"
-            } else if condition_lower.contains("mostly sunny") {
-                "M.SUN"
-            } else if condition_lower.contains("cloud") {
-                "CLDY"
-            } else if condition_lower.contains("thunder") {
-                "THRM"
-            } else if condition_lower.contains("rain") || condition_lower.contains("showers") {
-                "RAIN"
-            } else if condition_lower.contains("snow") {
-                "SNOW"
-            } else if condition_lower.contains("fog") || condition_lower.contains("mist") {
-                "FOG"
-            } else if condition_lower.contains("wind") {
-                "WIND"
-            } else {
-                "WX"
-            }
-        }
+        IconSet::NerdFont => "󰀦", // nf-md-alert
+        IconSet::Unicode => "⚠",
+        IconSet::Emoji => "⚠️",
+        IconSet::Text => "ALERT",
+    }
+}
+
+/// Arrow pointing the way the wind blows, for a direction it blows from.
+pub fn get_wind_arrow(degrees: f64, icon_set: &IconSet) -> &'static str {
+    const NERD_FONT: [&str; 8] = [
+        "", // nf-weather-direction_up
+        "", // nf-weather-direction_up_right
+        "", // nf-weather-direction_right
+        "", // nf-weather-direction_down_right
+        "", // nf-weather-direction_down
+        "", // nf-weather-direction_down_left
+        "", // nf-weather-direction_left
+        "", // nf-weather-direction_up_left
+    ];
+    const UNICODE: [&str; 8] = ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"];
+    const EMOJI: [&str; 8] = ["⬆️", "↗️", "➡️", "↘️", "⬇️", "↙️", "⬅️", "↖️"];
+    const TEXT: [&str; 8] = ["->N", "->NE", "->E", "->SE", "->S", "->SW", "->W", "->NW"];
+
+    let sector = wind::arrow_sector(degrees);
+    match icon_set {
+        IconSet::NerdFont => NERD_FONT[sector],
+        IconSet::Unicode => UNICODE[sector],
+        IconSet::Emoji => EMOJI[sector],
+        IconSet::Text => TEXT[sector],
+    }
+}
+
+pub fn get_trend_icon(trend: Trend, icon_set: &IconSet) -> &'static str {
+    match (icon_set, trend) {
+        (IconSet::NerdFont, Trend::Rising) => "󰁝", // nf-md-arrow_up
+        (IconSet::NerdFont, Trend::Falling) => "󰁅", // nf-md-arrow_down
+        (IconSet::NerdFont, Trend::Steady) => "󰁔", // nf-md-arrow_right
+        (IconSet::Unicode, Trend::Rising) => "↑",
+        (IconSet::Unicode, Trend::Falling) => "↓",
+        (IconSet::Unicode, Trend::Steady) => "→",
+        (IconSet::Emoji, Trend::Rising) => "⬆️",
+        (IconSet::Emoji, Trend::Falling) => "⬇️",
+        (IconSet::Emoji, Trend::Steady) => "➡️",
+        (IconSet::Text, Trend::Rising) => "+",
+        (IconSet::Text, Trend::Falling) => "-",
+        (IconSet::Text, Trend::Steady) => "=",
     }
 }
@@ -1,32 +1,193 @@
+
+pub mod astro;
+pub mod cache;
 pub mod client;
+pub mod comfort;
+pub mod condition;
 pub mod config;
+pub mod config_file;
+pub mod daemon;
 pub mod error;
+pub mod gridpoint;
+pub mod history;
 pub mod icons;
 pub mod models;
 pub mod output;
+pub mod rain;
+pub mod template;
+pub mod units;
+pub mod wind;
 
 // Re-export main types and functions
+pub use cache::Cache;
 pub use client::WeatherClient;
-pub use config::{Args, TemperatureUnit, IconSet, OutputFormat};
+pub use condition::WeatherCondition;
+pub use daemon::run_daemon;
+pub use config_file::ConfigFile;
+pub use config::{
+    Args, CacheCommand, Command, DistanceUnit, IconSet, OutputFormat, PrecipUnit, PressureUnit, TemperatureUnit, UnitSystem,
+    Units, WindUnit,
+};
 pub use error::WeatherError;
-pub use models::{Location, WeatherData, WaybarOutput};
-pub use output::create_output;
+pub use gridpoint::{Accumulation, GridLayer, GridSample, GridValue, GridpointData, ValidTime};
+pub use history::{History, HistoryPoint, Reading, Trend};
+pub use rain::{RainCheck, RainHour};
+pub use units::{Quantity, Unit};
+pub use models::{Alert, AlertSeverity, ForecastPeriod, Location, Station, WeatherData, WaybarOutput};
+pub use output::{
+    create_alerts_output, create_error_output, create_forecast_output, create_gridpoint_output, create_history_output,
+    create_hourly_output,
+    create_output, create_rain_check_output, create_stale_output,
+};
 
 use std::time::Duration;
 use tokio::time::sleep;
 
 const INITIAL_DELAY_MS: u64 = 3000;
+// Long enough to cover the three-hour trend window even with sparse reports
+const TREND_HISTORY_HOURS: u32 = 4;
 
-pub async fn run_weather_app(client: &WeatherClient, args: &Args) -> Result<String, WeatherError> {
-    // Wait for network if requested or add initial delay for resume scenarios
+/// What a run printed, and whether it fell back to the last known weather
+/// because the refresh itself failed.
+pub struct AppOutput {
+    pub output: String,
+    pub stale: bool,
+}
+
+pub async fn run_weather_app(client: &WeatherClient, args: &Args) -> Result<AppOutput, WeatherError> {
+    run(client, args, true).await
+}
+
+/// Like [`run_weather_app`], but skips the startup delay and network wait.
+/// Used by long-running callers that already paid them once.
+pub async fn refresh_weather_app(client: &WeatherClient, args: &Args) -> Result<AppOutput, WeatherError> {
+    run(client, args, false).await
+}
+
+async fn run(client: &WeatherClient, args: &Args, startup: bool) -> Result<AppOutput, WeatherError> {
+    if args.command.is_none() {
+        return run_current_conditions(client, args, startup).await;
+    }
+    let output = run_command(client, args, startup).await?;
+    Ok(AppOutput { output, stale: false })
+}
+
+async fn run_command(client: &WeatherClient, args: &Args, startup: bool) -> Result<String, WeatherError> {
+    if let Some(Command::Cache { action: CacheCommand::Clear }) = &args.command {
+        // Clear the real cache directory even when running with --no-cache
+        let cache = Cache::from_env();
+        let removed = cache.clear()?;
+        return Ok(match cache.dir() {
+            Some(dir) => format!("Removed {} cached responses from {}", removed, dir.display()),
+            None => "No cache directory configured".to_string(),
+        });
+    }
+
+    let location = prepare_location(client, args, startup).await?;
+
+    match &args.command {
+        Some(Command::Forecast) => {
+            let periods = client.get_forecast(&location).await?;
+            create_forecast_output(&location, &periods, args)
+        }
+        Some(Command::Hourly { hours }) => {
+            let periods = client.get_hourly_forecast(&location, *hours).await?;
+            create_hourly_output(&location, &periods, args)
+        }
+        Some(Command::Alerts) => {
+            let alerts = client.get_active_alerts(&location).await?;
+            create_alerts_output(&location, &alerts, args)
+        }
+        Some(Command::Gridpoint { layer, hours }) => {
+            let gridpoint = client.get_gridpoint(&location).await?;
+            create_gridpoint_output(&location, &gridpoint, layer.as_deref(), *hours, args)
+        }
+        Some(Command::History { hours }) => {
+            let history = client.get_observation_history(&location, *hours).await?;
+            create_history_output(&location, &history, args)
+        }
+        Some(Command::RainCheck { hours, threshold }) => {
+            let check = rain_check(client, &location, *hours, *threshold).await?;
+            create_rain_check_output(&location, &check, args)
+        }
+        Some(Command::Cache { .. }) | None => unreachable!("handled above"),
+    }
+}
+
+/// Runs `rain-check`, also returning whether precipitation is expected so
+/// the caller can turn the answer into an exit status. Skips the fixed
+/// startup delay, which is there for Waybar after resume, not for prompts
+/// and cron, but still honors `--wait-for-network`.
+pub async fn run_rain_check(
+    client: &WeatherClient,
+    args: &Args,
+    hours: u32,
+    threshold: f64,
+) -> Result<(String, bool), WeatherError> {
     if args.wait_for_network {
         client.wait_for_network().await?;
-    } else {
-        // Small delay to handle resume scenarios
-        sleep(Duration::from_millis(INITIAL_DELAY_MS)).await;
+    }
+    let location = prepare_location(client, args, false).await?;
+    let check = rain_check(client, &location, hours, threshold).await?;
+    Ok((create_rain_check_output(&location, &check, args)?, check.expected()))
+}
+
+async fn rain_check(client: &WeatherClient, location: &Location, hours: u32, threshold: f64) -> Result<RainCheck, WeatherError> {
+    let (periods, gridpoint) = tokio::join!(client.get_hourly_forecast(location, hours), client.get_gridpoint(location));
+    // QPF only sharpens the answer; the hourly chance alone still gives one
+    let gridpoint = gridpoint.ok();
+    RainCheck::new(&periods?, gridpoint.as_ref().and_then(|gridpoint| gridpoint.layer("quantitativePrecipitation")), threshold)
+}
+
+async fn prepare_location(client: &WeatherClient, args: &Args, startup: bool) -> Result<Location, WeatherError> {
+    // Wait for network if requested or add initial delay for resume scenarios
+    if startup {
+        if args.wait_for_network {
+            client.wait_for_network().await?;
+        } else {
+            // Small delay to handle resume scenarios
+            sleep(Duration::from_millis(INITIAL_DELAY_MS)).await;
+        }
     }
 
-    let location = client.resolve_location(args.zip.clone(), args.lat, args.lon).await?;
-    let weather = client.get_weather_data(&location).await?;
-    create_output(&location, &weather, args)
+    client.resolve_location(args.zip.clone(), args.lat, args.lon).await
+}
+
+/// Trends are decoration; a failed history request leaves them unset.
+async fn add_trends(client: &WeatherClient, weather: &mut WeatherData) {
+    let Some(station) = weather.station.clone() else {
+        return;
+    };
+    if let Ok(history) = client.get_station_history(station, TREND_HISTORY_HOURS).await {
+        weather.temperature_trend = history.temperature_trend;
+        weather.pressure_trend = history.pressure_trend;
+    }
+}
+
+async fn run_current_conditions(client: &WeatherClient, args: &Args, startup: bool) -> Result<AppOutput, WeatherError> {
+    let key = args.location_key();
+    let result = async {
+        let location = prepare_location(client, args, startup).await?;
+        let mut weather = client.get_weather_data(&location).await?;
+        if args.trend {
+            add_trends(client, &mut weather).await;
+        }
+        Ok::<_, WeatherError>((location, weather))
+    }.await;
+
+    match result {
+        Ok((location, weather)) => {
+            client.cache().store_last_known(&key, &location, &weather);
+            Ok(AppOutput { output: create_output(&location, &weather, args)?, stale: false })
+        }
+        Err(e) => {
+            // Fall back to the last known good weather while it's recent enough
+            match client.cache().load_last_known(&key) {
+                Some((location, weather)) if weather.age().to_std().is_ok_and(|age| age <= args.max_stale) => {
+                    Ok(AppOutput { output: create_stale_output(&location, &weather, args)?, stale: true })
+                }
+                _ => Err(e),
+            }
+        }
+    }
 }
@@ -1,33 +1,61 @@
-use clap::Parser;
-use nws_weather::{run_weather_app, Args, WeatherClient, OutputFormat};
-use nws_weather::models::WaybarOutput;
+use nws_weather::{create_error_output, run_daemon, run_rain_check, run_weather_app, Args, Cache, Command, WeatherClient};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-    let client = WeatherClient::new();
+    let (mut args, matches) = Args::parse_command_line();
+    let error_code = args.error_exit_code();
+    if let Err(e) = args.apply_config(&matches) {
+        eprintln!("Error: {}", e);
+        std::process::exit(error_code);
+    }
+    let cache = if args.no_cache { Cache::from_env().without_responses() } else { Cache::from_env() };
+    let mut client = WeatherClient::new()
+        .with_cache(cache)
+        .with_max_observation_age(args.max_observation_age);
+    if let Some(station) = &args.station {
+        client = client.with_station(station);
+    }
 
     // Validate input
-    if args.zip.is_none() && (args.lat.is_none() || args.lon.is_none()) {
-        eprintln!("Error: You must provide either --zip ZIPCODE or --lat LAT --lon LON");
-        std::process::exit(1);
+    if args.requires_location() && args.zip.is_none() && (args.lat.is_none() || args.lon.is_none()) {
+        eprintln!("Error: You must provide either --zip ZIPCODE, --lat LAT --lon LON or --location NAME");
+        std::process::exit(error_code);
+    }
+
+    if args.daemon {
+        if let Err(e) = run_daemon(&client, &args).await {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::RainCheck { hours, threshold }) = &args.command {
+        match run_rain_check(&client, &args, *hours, *threshold).await {
+            Ok((output, expected)) => {
+                println!("{}", output);
+                std::process::exit(if expected { 0 } else { 1 });
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                if let Some(fallback) = create_error_output(&e, &args) {
+                    println!("{}", fallback);
+                }
+                std::process::exit(error_code);
+            }
+        }
     }
 
     match run_weather_app(&client, &args).await {
-        Ok(output) => {
-            println!("{}", output);
+        Ok(app) => {
+            println!("{}", app.output);
         }
         Err(e) => {
             eprintln!("Error: {}", e);
             
             // Provide fallback output for Waybar to prevent breaking the bar
-            if matches!(args.format, OutputFormat::Waybar) {
-                let fallback = WaybarOutput {
-                    text: "Weather Error".to_string(),
-                    tooltip: format!("Failed to get weather data: {}", e),
-                    class: "weather-error".to_string(),
-                };
-                println!("{}", serde_json::to_string(&fallback).unwrap());
+            if let Some(fallback) = create_error_output(&e, &args) {
+                println!("{}", fallback);
             }
             
             std::process::exit(1);
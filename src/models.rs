@@ -1,5 +1,12 @@
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::condition::WeatherCondition;
+use crate::error::WeatherError;
+use crate::gridpoint::Accumulation;
+use crate::history::Trend;
+use crate::units::{Quantity, Unit};
+
 #[derive(Deserialize)]
 pub struct ZippopotamPlace {
     #[serde(rename = "place name")]
@@ -21,6 +28,8 @@ pub struct NWSPointProperties {
     pub grid_x: i64,
     #[serde(rename = "gridY")]
     pub grid_y: i64,
+    #[serde(rename = "forecastHourly")]
+    pub forecast_hourly: String,
     #[serde(rename = "observationStations")]
     pub observation_stations: String,
 }
@@ -30,13 +39,60 @@ pub struct NWSPointResponse {
     pub properties: NWSPointProperties,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, Debug)]
 pub struct ForecastPeriod {
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "startTime")]
+    pub start_time: String,
+    #[serde(rename = "endTime")]
+    pub end_time: String,
+    #[serde(rename = "isDaytime")]
+    pub is_daytime: bool,
     pub temperature: i64,
     #[serde(rename = "temperatureUnit")]
     pub temperature_unit: String,
+    #[serde(rename = "windSpeed")]
+    pub wind_speed: Option<String>,
+    #[serde(rename = "windDirection")]
+    pub wind_direction: Option<String>,
+    pub icon: Option<String>,
     #[serde(rename = "shortForecast")]
     pub short_forecast: String,
+    #[serde(rename = "detailedForecast", default)]
+    pub detailed_forecast: String,
+    #[serde(rename = "probabilityOfPrecipitation")]
+    pub probability_of_precipitation: Option<ObservationValue<f64>>,
+}
+
+impl ForecastPeriod {
+    pub fn weather_condition(&self) -> WeatherCondition {
+        WeatherCondition::classify(self.icon.as_deref(), &self.short_forecast)
+    }
+
+    /// Forecast temperature in °C; the forecast reports whole degrees in
+    /// `temperatureUnit` ("F" or "C").
+    pub fn temperature_celsius(&self) -> Result<f64, WeatherError> {
+        let unit = match self.temperature_unit.as_str() {
+            "F" => Unit::Fahrenheit,
+            "C" => Unit::Celsius,
+            other => return Err(WeatherError::UnknownUnit(other.to_string())),
+        };
+        Quantity::new(self.temperature as f64, unit).to(Unit::Celsius)
+    }
+
+    pub fn precipitation_chance(&self) -> Option<f64> {
+        self.probability_of_precipitation.as_ref().and_then(|p| p.value)
+    }
+
+    /// Period start in the location's local offset.
+    pub fn start(&self) -> Result<DateTime<FixedOffset>, WeatherError> {
+        DateTime::parse_from_rfc3339(&self.start_time).map_err(|_| WeatherError::InvalidTime(self.start_time.clone()))
+    }
+
+    pub fn end(&self) -> Result<DateTime<FixedOffset>, WeatherError> {
+        DateTime::parse_from_rfc3339(&self.end_time).map_err(|_| WeatherError::InvalidTime(self.end_time.clone()))
+    }
 }
 
 #[derive(Deserialize)]
@@ -44,6 +100,24 @@ pub struct ForecastProperties {
     pub periods: Vec<ForecastPeriod>,
 }
 
+impl ForecastProperties {
+    /// The day's high and low in °C. During the day that is today's high and
+    /// tonight's low; once the first period is a night (after the evening
+    /// switchover) it is tonight's low and tomorrow's high.
+    pub fn high_low(&self) -> Result<(Option<f64>, Option<f64>), WeatherError> {
+        let mut periods = self.periods.iter();
+        let (Some(first), second) = (periods.next(), periods.next()) else {
+            return Ok((None, None));
+        };
+        let second = second.filter(|period| period.is_daytime != first.is_daytime);
+
+        let (day, night) = if first.is_daytime { (Some(first), second) } else { (second, Some(first)) };
+        let high = day.map(ForecastPeriod::temperature_celsius).transpose()?;
+        let low = night.map(ForecastPeriod::temperature_celsius).transpose()?;
+        Ok((high, low))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ForecastResponse {
     pub properties: ForecastProperties,
@@ -51,13 +125,45 @@ pub struct ForecastResponse {
 
 #[derive(Deserialize)]
 pub struct StationFeature {
+    pub geometry: Option<PointGeometry>,
     pub properties: StationProperties,
 }
 
+/// GeoJSON point; `coordinates` is `[lon, lat]`.
+#[derive(Deserialize)]
+pub struct PointGeometry {
+    pub coordinates: Vec<f64>,
+}
+
 #[derive(Deserialize)]
 pub struct StationProperties {
     #[serde(rename = "stationIdentifier")]
     pub station_identifier: String,
+    #[serde(default)]
+    pub name: String,
+}
+
+impl StationFeature {
+    pub fn to_station(&self, location: &Location) -> Station {
+        let distance_km = self.geometry.as_ref().and_then(|geometry| match geometry.coordinates[..] {
+            [lon, lat, ..] => Some(location.distance_km(lat, lon)),
+            _ => None,
+        });
+        Station {
+            id: self.properties.station_identifier.clone(),
+            name: self.properties.name.clone(),
+            distance_km,
+        }
+    }
+}
+
+/// The observation station current conditions came from.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Station {
+    pub id: String,
+    pub name: String,
+    /// Great-circle distance from the requested location
+    pub distance_km: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -74,11 +180,65 @@ pub struct ObservationProperties {
     pub wind_speed: Option<ObservationValue<f64>>,
     #[serde(rename = "windDirection")]
     pub wind_direction: Option<ObservationValue<f64>>,
+    pub dewpoint: Option<ObservationValue<f64>>,
+    #[serde(rename = "heatIndex")]
+    pub heat_index: Option<ObservationValue<f64>>,
+    #[serde(rename = "windChill")]
+    pub wind_chill: Option<ObservationValue<f64>>,
+    #[serde(rename = "windGust")]
+    pub wind_gust: Option<ObservationValue<f64>>,
+    #[serde(rename = "barometricPressure")]
+    pub barometric_pressure: Option<ObservationValue<f64>>,
+    #[serde(rename = "seaLevelPressure")]
+    pub sea_level_pressure: Option<ObservationValue<f64>>,
+    pub visibility: Option<ObservationValue<f64>>,
+    #[serde(rename = "cloudLayers", default)]
+    pub cloud_layers: Option<Vec<ObservationCloudLayer>>,
+    #[serde(rename = "precipitationLastHour")]
+    pub precipitation_last_hour: Option<ObservationValue<f64>>,
+    #[serde(rename = "precipitationLast3Hours")]
+    pub precipitation_last_3_hours: Option<ObservationValue<f64>>,
+    #[serde(rename = "precipitationLast6Hours")]
+    pub precipitation_last_6_hours: Option<ObservationValue<f64>>,
+    #[serde(rename = "maxTemperatureLast24Hours")]
+    pub max_temperature_last_24_hours: Option<ObservationValue<f64>>,
+    #[serde(rename = "minTemperatureLast24Hours")]
+    pub min_temperature_last_24_hours: Option<ObservationValue<f64>>,
+    #[serde(rename = "textDescription")]
+    pub text_description: Option<String>,
+    pub timestamp: Option<DateTime<FixedOffset>>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, Debug)]
+pub struct ObservationCloudLayer {
+    pub base: ObservationValue<f64>,
+    pub amount: String,
+}
+
+#[derive(Deserialize, Clone, Debug)]
 pub struct ObservationValue<T> {
     pub value: Option<T>,
+    #[serde(rename = "unitCode")]
+    pub unit_code: Option<String>,
+}
+
+impl ObservationValue<f64> {
+    /// Typed quantity parsed from `unitCode`; errors on unknown or missing units.
+    pub fn quantity(&self) -> Result<Option<Quantity>, WeatherError> {
+        let Some(value) = self.value else {
+            return Ok(None);
+        };
+        let code = self
+            .unit_code
+            .as_deref()
+            .ok_or_else(|| WeatherError::UnknownUnit("missing unitCode".to_string()))?;
+        Ok(Some(Quantity::new(value, Unit::from_code(code)?)))
+    }
+
+    /// The value converted to `unit`, or `None` when the station reported null.
+    pub fn value_in(&self, unit: Unit) -> Result<Option<f64>, WeatherError> {
+        self.quantity()?.map(|quantity| quantity.to(unit)).transpose()
+    }
 }
 
 #[derive(Deserialize)]
@@ -86,25 +246,252 @@ pub struct ObservationResponse {
     pub properties: ObservationProperties,
 }
 
-#[derive(Debug)]
+#[derive(Deserialize)]
+pub struct ObservationCollection {
+    pub features: Vec<ObservationResponse>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertSeverity {
+    Extreme,
+    Severe,
+    Moderate,
+    Minor,
+    #[serde(other)]
+    Unknown,
+}
+
+impl AlertSeverity {
+    pub fn rank(&self) -> u8 {
+        match self {
+            AlertSeverity::Unknown => 0,
+            AlertSeverity::Minor => 1,
+            AlertSeverity::Moderate => 2,
+            AlertSeverity::Severe => 3,
+            AlertSeverity::Extreme => 4,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertSeverity::Unknown => "unknown",
+            AlertSeverity::Minor => "minor",
+            AlertSeverity::Moderate => "moderate",
+            AlertSeverity::Severe => "severe",
+            AlertSeverity::Extreme => "extreme",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Alert {
+    pub event: String,
+    pub severity: AlertSeverity,
+    pub urgency: String,
+    pub certainty: String,
+    pub headline: Option<String>,
+    pub description: Option<String>,
+    pub instruction: Option<String>,
+    pub onset: Option<DateTime<FixedOffset>>,
+    pub ends: Option<DateTime<FixedOffset>>,
+}
+
+#[derive(Deserialize)]
+pub struct AlertFeature {
+    pub properties: Alert,
+}
+
+#[derive(Deserialize)]
+pub struct AlertsResponse {
+    pub features: Vec<AlertFeature>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Location {
     pub lat: f64,
     pub lon: f64,
     pub name: String,
 }
 
-#[derive(Debug)]
+impl Location {
+    /// Haversine distance to another point, in km.
+    pub fn distance_km(&self, lat: f64, lon: f64) -> f64 {
+        const EARTH_RADIUS_KM: f64 = 6371.0;
+
+        let (lat1, lat2) = (self.lat.to_radians(), lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (lon - self.lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CloudLayer {
+    /// Sky cover code: SKC, CLR, FEW, SCT, BKN, OVC or VV
+    pub amount: String,
+    /// Cloud base in meters
+    pub base: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct WeatherData {
-    pub temperature: i64,
+    /// Air temperature in °C, at the precision the source reported
+    pub temperature: f64,
     pub condition: String,
+    /// NWS icon URL, which encodes the condition more reliably than the text
+    #[serde(default)]
+    pub icon: Option<String>,
     pub humidity: Option<f64>,
+    /// Wind speed in km/h
     pub wind_speed: Option<f64>,
     pub wind_direction: Option<f64>,
+    #[serde(default)]
+    pub dewpoint: Option<f64>,
+    #[serde(default)]
+    pub heat_index: Option<f64>,
+    #[serde(default)]
+    pub wind_chill: Option<f64>,
+    #[serde(default)]
+    pub feels_like: Option<f64>,
+    /// Wind gust in km/h
+    #[serde(default)]
+    pub wind_gust: Option<f64>,
+    /// Station pressure in Pa
+    #[serde(default)]
+    pub pressure: Option<f64>,
+    /// Sea level pressure in Pa
+    #[serde(default)]
+    pub sea_level_pressure: Option<f64>,
+    /// Visibility in meters
+    #[serde(default)]
+    pub visibility: Option<f64>,
+    #[serde(default)]
+    pub cloud_layers: Vec<CloudLayer>,
+    /// Precipitation totals in mm
+    #[serde(default)]
+    pub precipitation_last_hour: Option<f64>,
+    #[serde(default)]
+    pub precipitation_last_3_hours: Option<f64>,
+    #[serde(default)]
+    pub precipitation_last_6_hours: Option<f64>,
+    #[serde(default)]
+    pub max_temperature_24h: Option<f64>,
+    #[serde(default)]
+    pub min_temperature_24h: Option<f64>,
+    /// Station's own description of current conditions, e.g. "Light Rain"
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Forecast high and low in °C, see [`ForecastProperties::high_low`]
+    #[serde(default)]
+    pub high: Option<f64>,
+    #[serde(default)]
+    pub low: Option<f64>,
+    /// Chance of precipitation in the current forecast period, in percent
+    #[serde(default)]
+    pub precipitation_chance: Option<f64>,
+    #[serde(default)]
+    pub temperature_trend: Option<Trend>,
+    #[serde(default)]
+    pub pressure_trend: Option<Trend>,
+    #[serde(default)]
+    pub station: Option<Station>,
+    #[serde(default)]
+    pub observed_at: Option<DateTime<Utc>>,
+    /// Expected snow, ice and precipitation totals, shortest window first
+    #[serde(default)]
+    pub accumulations: Vec<Accumulation>,
+    pub alerts: Vec<Alert>,
+    pub updated: DateTime<Utc>,
+}
+
+impl WeatherData {
+    pub fn weather_condition(&self) -> WeatherCondition {
+        WeatherCondition::classify(self.icon.as_deref(), &self.condition)
+    }
+
+    pub fn age(&self) -> chrono::Duration {
+        Utc::now() - self.updated
+    }
+
+    /// How old the station reading was, or `None` when current conditions
+    /// fell back to the forecast.
+    pub fn observation_age(&self) -> Option<chrono::Duration> {
+        self.station.as_ref()?;
+        self.observed_at.map(|observed_at| Utc::now() - observed_at)
+    }
+
+    pub fn highest_alert_severity(&self) -> Option<AlertSeverity> {
+        self.alerts.iter().map(|alert| alert.severity).max_by_key(AlertSeverity::rank)
+    }
 }
 
 #[derive(Serialize)]
 pub struct WaybarOutput {
     pub text: String,
     pub tooltip: String,
-    pub class: String,
+    pub class: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(is_daytime: bool, temperature: i64, unit: &str) -> ForecastPeriod {
+        serde_json::from_value(serde_json::json!({
+            "startTime": "2026-10-16T06:00:00-07:00",
+            "endTime": "2026-10-16T18:00:00-07:00",
+            "isDaytime": is_daytime,
+            "temperature": temperature,
+            "temperatureUnit": unit,
+            "windSpeed": null,
+            "windDirection": null,
+            "icon": null,
+            "shortForecast": "",
+            "probabilityOfPrecipitation": null
+        }))
+        .unwrap()
+    }
+
+    fn high_low(periods: Vec<ForecastPeriod>) -> (Option<f64>, Option<f64>) {
+        ForecastProperties { periods }.high_low().unwrap()
+    }
+
+    fn observation(value: Option<f64>, unit_code: Option<&str>) -> ObservationValue<f64> {
+        serde_json::from_value(serde_json::json!({ "value": value, "unitCode": unit_code })).unwrap()
+    }
+
+    #[test]
+    fn observation_values_convert_by_unit_code() {
+        let mph = observation(Some(16.09), Some("wmoUnit:km_h-1")).value_in(Unit::MilesPerHour).unwrap();
+        assert!((mph.unwrap() - 10.0).abs() < 0.01);
+        assert_eq!(observation(None, Some("wmoUnit:km_h-1")).value_in(Unit::MilesPerHour).unwrap(), None);
+        assert!(observation(Some(5.0), Some("wmoUnit:furlong_h-1")).value_in(Unit::MilesPerHour).is_err());
+        assert!(observation(Some(5.0), None).value_in(Unit::MilesPerHour).is_err());
+    }
+
+    #[test]
+    fn daytime_first_pairs_today_with_tonight() {
+        assert_eq!(high_low(vec![period(true, 30, "C"), period(false, 10, "C")]), (Some(30.0), Some(10.0)));
+    }
+
+    #[test]
+    fn night_first_pairs_tonight_with_tomorrow() {
+        // After the evening switchover the forecast starts with tonight
+        assert_eq!(high_low(vec![period(false, 10, "C"), period(true, 25, "C")]), (Some(25.0), Some(10.0)));
+    }
+
+    #[test]
+    fn unpaired_periods_give_one_side() {
+        assert_eq!(high_low(vec![period(true, 30, "C")]), (Some(30.0), None));
+        assert_eq!(high_low(vec![period(false, 10, "C"), period(false, 8, "C")]), (None, Some(10.0)));
+        assert_eq!(high_low(Vec::new()), (None, None));
+    }
+
+    #[test]
+    fn converts_and_rejects_temperature_units() {
+        assert_eq!(period(true, 212, "F").temperature_celsius().unwrap(), 100.0);
+        assert!(period(true, 300, "K").temperature_celsius().is_err());
+        assert!(ForecastProperties { periods: vec![period(true, 300, "K")] }.high_low().is_err());
+    }
 }
@@ -1,74 +1,1189 @@
-use crate::config::{Args, OutputFormat, TemperatureUnit};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::Serialize;
+
+use crate::astro::{self, AstroData};
+use crate::config::{Args, OutputFormat, TemperatureUnit, Units, WindUnit};
 use crate::error::WeatherError;
-use crate::icons::get_weather_icon;
-use crate::models::{Location, WeatherData, WaybarOutput};
+use crate::gridpoint::{self, Accumulation, GridLayer, GridSample, GridpointData};
+use crate::history::{History, HistoryPoint, Trend};
+use crate::icons::{get_alert_icon, get_condition_icon, get_trend_icon, get_wind_arrow};
+use crate::condition::WeatherCondition;
+use crate::models::{Alert, AlertSeverity, CloudLayer, ForecastPeriod, Location, Station, WeatherData, WaybarOutput};
+use crate::rain::RainCheck;
+use crate::template::{self, TemplateValues};
+use crate::units::{convert, Dimension, Unit};
+use crate::wind::{self, beaufort_number};
+
+/// Converts a Celsius temperature to `unit`, rounding exactly once.
+pub fn temperature_value(temp_c: f64, unit: &TemperatureUnit, precision: usize) -> f64 {
+    // Adding 0.0 turns -0.0 into 0.0 so "-0" is never shown
+    round_to(convert(temp_c, Unit::Celsius, unit.unit()), precision) + 0.0
+}
+
+pub fn format_temperature(temp_c: f64, unit: &TemperatureUnit, precision: usize) -> (String, &'static str) {
+    (format!("{:.*}", precision, temperature_value(temp_c, unit, precision)), unit.symbol())
+}
+
+fn period_temperature_value(period: &ForecastPeriod, unit: &TemperatureUnit, precision: usize) -> Result<f64, WeatherError> {
+    Ok(temperature_value(period.temperature_celsius()?, unit, precision))
+}
+
+pub fn format_period_temperature(
+    period: &ForecastPeriod,
+    unit: &TemperatureUnit,
+    precision: usize,
+) -> Result<(String, &'static str), WeatherError> {
+    Ok(format_temperature(period.temperature_celsius()?, unit, precision))
+}
 
-// Temperature conversion
-const CELSIUS_TO_FAHRENHEIT_MULTIPLIER: f64 = 9.0 / 5.0;
-const CELSIUS_TO_FAHRENHEIT_OFFSET: f64 = 32.0;
+/// Whole numbers stay integers in JSON when no decimals were asked for.
+fn json_number(value: f64, precision: usize) -> serde_json::Value {
+    if precision == 0 {
+        (value as i64).into()
+    } else {
+        value.into()
+    }
+}
+
+fn format_period_wind(period: &ForecastPeriod, unit: &WindUnit) -> String {
+    match (&period.wind_speed, &period.wind_direction) {
+        (Some(speed), Some(dir)) if !dir.is_empty() => format!("{} {}", convert_wind_text(speed, unit), dir),
+        (Some(speed), _) => convert_wind_text(speed, unit),
+        _ => String::new(),
+    }
+}
 
-pub fn format_temperature(temp_c: i64, unit: &TemperatureUnit) -> (i64, &'static str) {
-    match unit {
-        TemperatureUnit::Celsius => (temp_c, "°C"),
-        TemperatureUnit::Fahrenheit => {
-            let temp_f = (temp_c as f64 * CELSIUS_TO_FAHRENHEIT_MULTIPLIER + CELSIUS_TO_FAHRENHEIT_OFFSET).round() as i64;
-            (temp_f, "°F")
+/// Speeds in forecast wind text such as "5 to 10 mph", in km/h; `None` if
+/// the text isn't in that format.
+fn parse_wind_text(text: &str) -> Option<Vec<f64>> {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    let from = match words.pop() {
+        Some("mph") => Unit::MilesPerHour,
+        Some("km/h") => Unit::KilometersPerHour,
+        _ => return None,
+    };
+
+    let mut speeds = Vec::with_capacity(words.len());
+    for word in words {
+        match word.parse::<f64>() {
+            Ok(speed) => speeds.push(convert(speed, from, Unit::KilometersPerHour)),
+            Err(_) if word == "to" => {}
+            Err(_) => return None,
         }
     }
+    Some(speeds)
 }
 
-pub fn create_output(location: &Location, weather: &WeatherData, args: &Args) -> Result<String, WeatherError> {
-    let icon = get_weather_icon(&weather.condition, &args.icons);
-    let (temp, unit) = format_temperature(weather.temperature, &args.unit);
-    
+/// Rewrites forecast wind text such as "5 to 10 mph" in `unit`, leaving text
+/// in an unrecognized format untouched.
+fn convert_wind_text(text: &str, unit: &WindUnit) -> String {
+    match parse_wind_text(text) {
+        Some(speeds) if !speeds.is_empty() => {
+            let speeds: Vec<String> = speeds.iter().map(|&kmh| format_wind_value(kmh, unit)).collect();
+            format!("{} {}", speeds.join(" to "), unit.symbol())
+        }
+        _ => text.to_string(),
+    }
+}
+
+fn format_precipitation_chance(period: &ForecastPeriod) -> String {
+    period.precipitation_chance()
+        .map(|pop| format!("{:.0}%", pop))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_table(headers: &[&str], rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells.iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![format_row(headers.to_vec())];
+    lines.extend(rows.iter().map(|row| format_row(row.iter().map(String::as_str).collect())));
+    lines
+}
+
+fn hour_label(period: &ForecastPeriod) -> String {
+    DateTime::parse_from_rfc3339(&period.start_time)
+        .map(|time| time.format("%a %-I %p").to_string())
+        .unwrap_or_else(|_| period.start_time.clone())
+}
+
+pub fn create_forecast_output(location: &Location, periods: &[ForecastPeriod], args: &Args) -> Result<String, WeatherError> {
+    create_periods_output(location, periods, args, "Period", |period| period.name.clone(), "weather-forecast")
+}
+
+pub fn create_hourly_output(location: &Location, periods: &[ForecastPeriod], args: &Args) -> Result<String, WeatherError> {
+    create_periods_output(location, periods, args, "Time", hour_label, "weather-hourly")
+}
+
+fn create_periods_output(
+    location: &Location,
+    periods: &[ForecastPeriod],
+    args: &Args,
+    label_header: &str,
+    label: fn(&ForecastPeriod) -> String,
+    class: &str,
+) -> Result<String, WeatherError> {
+    let units = args.units();
+    match args.format {
+        OutputFormat::Plain => {
+            let rows = periods.iter().map(|period| {
+                let icon = get_condition_icon(period.weather_condition(), period.is_daytime, &args.icons);
+                let (temp, unit) = format_period_temperature(period, &units.temperature, args.precision)?;
+                Ok(vec![
+                    label(period),
+                    format!("{} {}{}", icon, temp, unit),
+                    format_precipitation_chance(period),
+                    format_period_wind(period, &units.wind),
+                    period.short_forecast.clone(),
+                ])
+            }).collect::<Result<Vec<Vec<String>>, WeatherError>>()?;
+
+            let table = format_table(&[label_header, "Temp", "Precip", "Wind", "Forecast"], &rows);
+            let mut lines = vec![location.name.clone(), table[0].clone()];
+            for (row, period) in table[1..].iter().zip(periods) {
+                lines.push(row.clone());
+                if args.detailed && !period.detailed_forecast.is_empty() {
+                    lines.push(format!("    {}", period.detailed_forecast));
+                }
+            }
+
+            Ok(lines.join("\n"))
+        }
+        OutputFormat::Json => {
+            let output = periods.iter().map(|period| {
+                let temp = period_temperature_value(period, &units.temperature, args.precision)?;
+                let speeds = period.wind_speed.as_deref().and_then(parse_wind_text).unwrap_or_default();
+                let speed = |kmh: Option<&f64>| kmh.map(|&kmh| round_to(wind_value(kmh, &units.wind), 1));
+                Ok(serde_json::json!({
+                    "name": period.name,
+                    "start_time": period.start_time,
+                    "end_time": period.end_time,
+                    "is_daytime": period.is_daytime,
+                    "temperature": json_number(temp, args.precision),
+                    "unit": units.temperature.symbol(),
+                    "wind_speed": period.wind_speed.as_deref().map(|text| convert_wind_text(text, &units.wind)),
+                    "wind_speed_min": speed(speeds.first()),
+                    "wind_speed_max": speed(speeds.last()),
+                    "wind_direction": period.wind_direction,
+                    "condition": period.short_forecast,
+                    "condition_code": period.weather_condition(),
+                    "detailed_forecast": period.detailed_forecast,
+                    "precipitation_chance": period.precipitation_chance(),
+                    "icon": get_condition_icon(period.weather_condition(), period.is_daytime, &args.icons),
+                    "icon_url": period.icon,
+                    "units": {
+                        "temperature": units.temperature.symbol(),
+                        "wind": units.wind.symbol()
+                    }
+                }))
+            }).collect::<Result<Vec<_>, WeatherError>>()?;
+            Ok(serde_json::to_string_pretty(&output)?)
+        }
+        OutputFormat::Waybar => {
+            let first = periods.first().ok_or(WeatherError::NoWeatherData)?;
+            let (temp, unit) = format_period_temperature(first, &units.temperature, args.precision)?;
+            let text = format!("{} {}{}", get_condition_icon(first.weather_condition(), first.is_daytime, &args.icons), temp, unit);
+
+            let mut tooltip_parts = vec![location.name.clone()];
+            for period in periods {
+                let (temp, unit) = format_period_temperature(period, &units.temperature, args.precision)?;
+                let summary = if args.detailed && !period.detailed_forecast.is_empty() {
+                    &period.detailed_forecast
+                } else {
+                    &period.short_forecast
+                };
+                let mut line = format!("{}: {}{} {}", label(period), temp, unit, summary);
+                if let Some(pop) = period.precipitation_chance() {
+                    line.push_str(&format!(", {:.0}% precip", pop));
+                }
+                let wind = format_period_wind(period, &units.wind);
+                if !wind.is_empty() {
+                    line.push_str(&format!(", wind {}", wind));
+                }
+                tooltip_parts.push(line);
+            }
+
+            let output = WaybarOutput {
+                text,
+                tooltip: tooltip_parts.join("\n"),
+                class: vec![class.to_string()],
+            };
+
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+}
+
+fn alert_class(severity: AlertSeverity) -> String {
+    format!("weather-alert-{}", severity.as_str())
+}
+
+fn format_alert_summary(alert: &Alert) -> String {
+    match alert.ends {
+        Some(ends) => format!("{} until {}", alert.event, ends.format("%a %-I:%M %p")),
+        None => alert.event.clone(),
+    }
+}
+
+pub fn create_alerts_output(location: &Location, alerts: &[Alert], args: &Args) -> Result<String, WeatherError> {
+    let highest = alerts.iter().map(|alert| alert.severity).max_by_key(AlertSeverity::rank);
+
+    match args.format {
+        OutputFormat::Plain => {
+            if alerts.is_empty() {
+                return Ok(format!("No active alerts for {}", location.name));
+            }
+
+            let mut lines = Vec::new();
+            for alert in alerts {
+                lines.push(format!(
+                    "{} {} ({}, {}, {})",
+                    get_alert_icon(&args.icons),
+                    format_alert_summary(alert),
+                    alert.severity.as_str(),
+                    alert.urgency.to_lowercase(),
+                    alert.certainty.to_lowercase()
+                ));
+                if let Some(headline) = &alert.headline {
+                    lines.push(format!("  {}", headline));
+                }
+                if args.detailed {
+                    if let Some(description) = &alert.description {
+                        lines.push(String::new());
+                        lines.push(description.clone());
+                    }
+                    if let Some(instruction) = &alert.instruction {
+                        lines.push(String::new());
+                        lines.push(instruction.clone());
+                    }
+                }
+                lines.push(String::new());
+            }
+
+            Ok(lines.join("\n").trim_end().to_string())
+        }
+        OutputFormat::Json => {
+            Ok(serde_json::to_string_pretty(alerts)?)
+        }
+        OutputFormat::Waybar => {
+            let output = match highest {
+                Some(severity) => {
+                    let text = if alerts.len() == 1 {
+                        format!("{} {}", get_alert_icon(&args.icons), alerts[0].event)
+                    } else {
+                        format!("{} {} alerts", get_alert_icon(&args.icons), alerts.len())
+                    };
+                    let tooltip = alerts.iter()
+                        .map(|alert| {
+                            let summary = format_alert_summary(alert);
+                            match (&alert.headline, args.detailed) {
+                                (Some(headline), true) => format!("{}\n{}", summary, headline),
+                                _ => summary,
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    WaybarOutput {
+                        text,
+                        tooltip,
+                        class: vec![alert_class(severity)],
+                    }
+                }
+                None => WaybarOutput {
+                    text: String::new(),
+                    tooltip: format!("No active alerts for {}", location.name),
+                    class: vec!["weather-alert-none".to_string()],
+                },
+            };
+
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+}
+
+fn format_trend(trend: Option<Trend>, args: &Args) -> String {
+    match trend {
+        Some(trend) => format!(" {} {}", get_trend_icon(trend, &args.icons), trend.as_str()),
+        None => String::new(),
+    }
+}
+
+pub fn create_history_output(location: &Location, history: &History, args: &Args) -> Result<String, WeatherError> {
+    let units = args.units();
+    let temperature = |temp_c: Option<f64>| match temp_c {
+        Some(t) => format_temperature(t, &units.temperature, args.precision).0 + units.temperature.symbol(),
+        None => "-".to_string(),
+    };
+    let pressure = |pa: Option<f64>| pa.map(|pa| format_pressure(pa, &units)).unwrap_or_else(|| "-".to_string());
+    let clock = |point: &HistoryPoint| point.time.with_timezone(&Local).format("%a %H:%M").to_string();
+
+    let summary = vec![
+        format!("{}: {}", location.name, format_station(&history.station, &units)),
+        format!("High: {}  Low: {}", temperature(history.high), temperature(history.low)),
+        format!("Temperature: {}{}", temperature(history.latest_temperature()), format_trend(history.temperature_trend, args)),
+        format!("Pressure: {}{}", pressure(history.latest_pressure()), format_trend(history.pressure_trend, args)),
+    ];
+
+    match args.format {
+        OutputFormat::Plain => {
+            let rows: Vec<Vec<String>> = history.hourly().into_iter().rev()
+                .map(|point| vec![clock(point), temperature(point.temperature), pressure(point.pressure)])
+                .collect();
+            let mut lines = summary;
+            lines.push(String::new());
+            lines.extend(format_table(&["Time", "Temp", "Pressure"], &rows));
+            Ok(lines.join("\n"))
+        }
+        OutputFormat::Json => {
+            let series: Vec<_> = history.points.iter().map(|point| serde_json::json!({
+                "time": point.time,
+                "temperature": json_temperature(point.temperature, &units, args.precision),
+                "pressure": point.pressure.map(|pa| pressure_value(pa, &units)),
+            })).collect();
+            let output = serde_json::json!({
+                "location": location.name,
+                "station": history.station,
+                "high": json_temperature(history.high, &units, args.precision),
+                "low": json_temperature(history.low, &units, args.precision),
+                "temperature_trend": history.temperature_trend,
+                "pressure_trend": history.pressure_trend,
+                "units": {
+                    "temperature": units.temperature.symbol(),
+                    "pressure": units.pressure.symbol()
+                },
+                "series": series
+            });
+            Ok(serde_json::to_string_pretty(&output)?)
+        }
+        OutputFormat::Waybar => {
+            let mut text = temperature(history.latest_temperature());
+            if let Some(trend) = history.temperature_trend {
+                text.push_str(&format!(" {}", get_trend_icon(trend, &args.icons)));
+            }
+
+            let mut tooltip_parts = summary;
+            if args.detailed {
+                tooltip_parts.extend(history.hourly().into_iter().rev().map(|point| {
+                    format!("{}  {}  {}", clock(point), temperature(point.temperature), pressure(point.pressure))
+                }));
+            }
+
+            let output = WaybarOutput {
+                text,
+                tooltip: tooltip_parts.join("\n"),
+                class: vec!["weather-history".to_string()],
+            };
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+}
+
+fn format_grid_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "-".to_string(),
+        serde_json::Value::Number(number) => {
+            let text = format!("{:.1}", number.as_f64().unwrap_or_default());
+            text.strip_suffix(".0").unwrap_or(&text).to_string()
+        }
+        serde_json::Value::String(text) => text.clone(),
+        // Weather and hazard entries, e.g. {"coverage": "chance", "weather": "rain_showers", ...}
+        serde_json::Value::Array(items) => items.iter()
+            .map(|item| match item {
+                serde_json::Value::Object(fields) => fields.values()
+                    .filter_map(serde_json::Value::as_str)
+                    .map(|text| text.replace('_', " "))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                other => format_grid_value(other),
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn grid_unit_label(layer: &GridLayer) -> String {
+    match layer.unit() {
+        Ok(Some(unit)) => unit.symbol().to_string(),
+        Ok(None) => String::new(),
+        // Show codes this crate doesn't know as-is rather than failing the listing
+        Err(_) => layer.uom.clone().unwrap_or_default(),
+    }
+}
+
+/// The unit and decimals to show a numeric layer in, following the display
+/// units. `None` keeps the layer's own unit: non-numeric layers, unknown
+/// codes, ratios and angles, and wind in Beaufort, which is a scale.
+fn grid_display_unit(name: &str, layer: &GridLayer, args: &Args) -> Option<(Unit, usize)> {
+    let from = layer.unit().ok()??;
+    let units = args.units();
+    match from.dimension() {
+        Dimension::Temperature => Some((units.temperature.unit(), args.precision)),
+        Dimension::Speed => units.wind.unit().map(|unit| (unit, 1)),
+        Dimension::Pressure => Some((units.pressure.unit(), units.pressure.decimals())),
+        Dimension::Length if layer.accumulation => Some((units.precipitation.unit(), units.precipitation.decimals())),
+        Dimension::Length if name == "visibility" => Some((units.distance.unit(), 1)),
+        // Heights such as mixingHeight or waveHeight
+        Dimension::Length => Some((units.distance.short_unit(), 0)),
+        Dimension::Ratio | Dimension::Angle => None,
+    }
+}
+
+pub fn create_gridpoint_output(
+    location: &Location,
+    gridpoint: &GridpointData,
+    layer: Option<&str>,
+    hours: u32,
+    args: &Args,
+) -> Result<String, WeatherError> {
+    let Some(name) = layer else {
+        return create_grid_layers_output(location, gridpoint, args);
+    };
+    let layer = gridpoint.layer(name).ok_or_else(|| {
+        WeatherError::UnknownLayer(format!("{} (run without --layer to list layers)", name))
+    })?;
+
+    let start = gridpoint::current_hour();
+    let end = start + chrono::Duration::hours(hours.into());
+    let display_unit = grid_display_unit(name, layer, args);
+    let (samples, unit) = match display_unit {
+        Some((to, decimals)) => {
+            let samples = layer.hourly_in(to, start, end)?.into_iter()
+                .map(|sample| GridSample { time: sample.time, value: round_to(sample.value, decimals).into() })
+                .collect();
+            (samples, to.symbol().to_string())
+        }
+        None => (layer.hourly(start, end), grid_unit_label(layer)),
+    };
+    let clock = |time: DateTime<Utc>| time.with_timezone(&Local).format("%a %-I %p").to_string();
+    let with_unit = |value: &serde_json::Value| match (value.as_f64(), display_unit, unit.is_empty()) {
+        (Some(number), Some((_, decimals)), _) => format!("{:.*} {}", decimals, number, unit),
+        (Some(_), None, false) => format!("{} {}", format_grid_value(value), unit),
+        _ => format_grid_value(value),
+    };
+
     match args.format {
         OutputFormat::Plain => {
-            Ok(format!("{} {}{}  {}", icon, temp, unit, weather.condition))
+            let rows: Vec<Vec<String>> = samples.iter()
+                .map(|sample| vec![clock(sample.time), with_unit(&sample.value)])
+                .collect();
+            let mut lines = vec![format!("{}: {}", location.name, name)];
+            lines.extend(format_table(&["Time", "Value"], &rows));
+            Ok(lines.join("\n"))
         }
         OutputFormat::Json => {
             let output = serde_json::json!({
                 "location": location.name,
-                "temperature": temp,
+                "layer": name,
+                "uom": layer.uom,
                 "unit": unit,
-                "condition": weather.condition,
-                "icon": icon,
-                "humidity": weather.humidity,
-                "wind_speed": weather.wind_speed,
-                "wind_direction": weather.wind_direction
+                "update_time": gridpoint.update_time,
+                "samples": samples
+            });
+            Ok(serde_json::to_string_pretty(&output)?)
+        }
+        OutputFormat::Waybar => {
+            let first = samples.first().ok_or(WeatherError::NoWeatherData)?;
+            let mut tooltip_parts = vec![format!("{}: {}", location.name, name)];
+            tooltip_parts.extend(samples.iter().map(|sample| format!("{}: {}", clock(sample.time), with_unit(&sample.value))));
+            let output = WaybarOutput {
+                text: with_unit(&first.value),
+                tooltip: tooltip_parts.join("\n"),
+                class: vec!["weather-gridpoint".to_string()],
+            };
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+}
+
+fn create_grid_layers_output(location: &Location, gridpoint: &GridpointData, args: &Args) -> Result<String, WeatherError> {
+    let rows: Vec<Vec<String>> = gridpoint.layers.iter()
+        .map(|(name, layer)| vec![name.clone(), grid_unit_label(layer), layer.values.len().to_string()])
+        .collect();
+
+    match args.format {
+        OutputFormat::Plain => {
+            let mut lines = vec![format!("{}: {} gridpoint layers", location.name, rows.len())];
+            lines.extend(format_table(&["Layer", "Unit", "Intervals"], &rows));
+            Ok(lines.join("\n"))
+        }
+        OutputFormat::Json => {
+            let layers: Vec<_> = gridpoint.layers.iter().map(|(name, layer)| serde_json::json!({
+                "name": name,
+                "uom": layer.uom,
+                "intervals": layer.values.len(),
+            })).collect();
+            let output = serde_json::json!({
+                "location": location.name,
+                "update_time": gridpoint.update_time,
+                "layers": layers
             });
             Ok(serde_json::to_string_pretty(&output)?)
         }
         OutputFormat::Waybar => {
-            let text = format!("{} {}{}", icon, temp, unit);
+            let output = WaybarOutput {
+                text: format!("{} layers", rows.len()),
+                tooltip: gridpoint.layer_names().collect::<Vec<_>>().join("\n"),
+                class: vec!["weather-gridpoint".to_string()],
+            };
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+}
+
+/// Hour label for rain-check times, with the weekday once it isn't the
+/// first hour's day.
+fn rain_clock(check: &RainCheck, time: DateTime<FixedOffset>) -> String {
+    let same_day = check.hours.first().is_some_and(|first| first.start.date_naive() == time.date_naive());
+    time.format(if same_day { "%-I %p" } else { "%a %-I %p" }).to_string()
+}
+
+/// "now" or e.g. "from 3 PM"; `None` if no precipitation is expected.
+fn rain_start(check: &RainCheck) -> Option<String> {
+    let start = check.start?;
+    if check.hours.first().is_some_and(|first| first.start == start) {
+        Some("now".to_string())
+    } else {
+        Some(format!("from {}", rain_clock(check, start)))
+    }
+}
+
+/// e.g. "Precipitation expected from 3 PM until 5 PM (up to 70%)"
+fn format_rain_summary(check: &RainCheck) -> String {
+    let mut summary = match (rain_start(check), check.stop, check.end()) {
+        (Some(from), Some(stop), _) => format!("Precipitation expected {} until {}", from, rain_clock(check, stop)),
+        (Some(from), None, Some(end)) => format!("Precipitation expected {} through {}", from, rain_clock(check, end)),
+        (Some(from), None, None) => format!("Precipitation expected {}", from),
+        (None, _, _) if check.hours.len() == 1 => "No precipitation expected in the next hour".to_string(),
+        (None, _, _) => format!("No precipitation expected in the next {} hours", check.hours.len()),
+    };
+    if let Some(chance) = check.max_chance() {
+        summary.push_str(&format!(" (up to {:.0}%)", chance));
+    }
+    summary
+}
+
+pub fn create_rain_check_output(location: &Location, check: &RainCheck, args: &Args) -> Result<String, WeatherError> {
+    let units = args.units();
+    let summary = format_rain_summary(check);
+    let chance = |chance: Option<f64>| chance.map(|pop| format!("{:.0}%", pop)).unwrap_or_else(|| "-".to_string());
+    let amount = |mm: Option<f64>| mm.map(|mm| format_precipitation(mm, &units)).unwrap_or_else(|| "-".to_string());
+    let clock = |time: DateTime<FixedOffset>| time.format("%a %-I %p").to_string();
+
+    match args.format {
+        OutputFormat::Plain => {
+            let mut lines = vec![summary];
+            if args.detailed {
+                let rows: Vec<Vec<String>> = check.hours.iter()
+                    .map(|hour| vec![clock(hour.start), chance(hour.chance), amount(hour.amount)])
+                    .collect();
+                lines.push(String::new());
+                lines.extend(format_table(&["Time", "Precip", "Amount"], &rows));
+            }
+            Ok(lines.join("\n"))
+        }
+        OutputFormat::Json => {
+            let hours: Vec<_> = check.hours.iter().map(|hour| serde_json::json!({
+                "start": hour.start,
+                "end": hour.end,
+                "precipitation_chance": hour.chance,
+                "amount": hour.amount.map(|mm| precipitation_value(mm, &units)),
+                "wet": hour.wet,
+            })).collect();
+            let output = serde_json::json!({
+                "location": location.name,
+                "expected": check.expected(),
+                "start": check.start,
+                "stop": check.stop,
+                "max_chance": check.max_chance(),
+                "summary": summary,
+                "units": {
+                    "precipitation": units.precipitation.unit().symbol()
+                },
+                "hours": hours
+            });
+            Ok(serde_json::to_string_pretty(&output)?)
+        }
+        OutputFormat::Waybar => {
+            let condition = if check.expected() { WeatherCondition::Rain } else { WeatherCondition::Clear };
+            let icon = get_condition_icon(condition, astro::is_daylight(location, Utc::now()), &args.icons);
+
+            let mut tooltip_parts = vec![format!("{}: {}", location.name, summary)];
+            if args.detailed {
+                tooltip_parts.extend(check.hours.iter().map(|hour| {
+                    format!("{}  {}  {}", clock(hour.start), chance(hour.chance), amount(hour.amount))
+                }));
+            }
+
+            let text = match rain_start(check) {
+                Some(from) => format!("{} {}", icon, from),
+                None => format!("{} dry", icon),
+            };
+
+            let output = WaybarOutput {
+                text,
+                tooltip: tooltip_parts.join("\n"),
+                class: vec![if check.expected() { "weather-rain-expected" } else { "weather-rain-none" }.to_string()],
+            };
+            Ok(serde_json::to_string(&output)?)
+        }
+    }
+}
+
+pub fn create_error_output(error: &WeatherError, args: &Args) -> Option<String> {
+    // Only Waybar needs a placeholder to prevent breaking the bar
+    if !matches!(args.format, OutputFormat::Waybar) {
+        return None;
+    }
+
+    let output = WaybarOutput {
+        text: "Weather Error".to_string(),
+        tooltip: format!("Failed to get weather data: {}", error),
+        class: vec!["weather-error".to_string()],
+    };
+    serde_json::to_string(&output).ok()
+}
+
+pub fn format_age(age: chrono::Duration) -> String {
+    let minutes = age.num_minutes();
+    if minutes < 1 {
+        "just now".to_string()
+    } else if minutes < 60 {
+        format!("{} min ago", minutes)
+    } else if minutes < 48 * 60 {
+        format!("{} h ago", minutes / 60)
+    } else {
+        format!("{} d ago", minutes / (24 * 60))
+    }
+}
+
+pub fn create_output(location: &Location, weather: &WeatherData, args: &Args) -> Result<String, WeatherError> {
+    render_output(location, weather, args, false)
+}
+
+/// Renders last known good weather after a failed refresh, marked with its age.
+pub fn create_stale_output(location: &Location, weather: &WeatherData, args: &Args) -> Result<String, WeatherError> {
+    render_output(location, weather, args, true)
+}
+
+fn wind_value(kmh: f64, unit: &WindUnit) -> f64 {
+    match unit.unit() {
+        Some(to) => convert(kmh, Unit::KilometersPerHour, to),
+        None => beaufort_number(kmh) as f64,
+    }
+}
+
+fn format_wind_value(kmh: f64, unit: &WindUnit) -> String {
+    format!("{:.0}", wind_value(kmh, unit))
+}
+
+fn format_wind_speed(kmh: f64, unit: &WindUnit) -> String {
+    format!("{} {}", format_wind_value(kmh, unit), unit.symbol())
+}
+
+/// A Celsius temperature as a JSON number in display units.
+fn json_temperature(temp_c: Option<f64>, units: &Units, precision: usize) -> Option<serde_json::Value> {
+    temp_c.map(|t| json_number(temperature_value(t, &units.temperature, precision), precision))
+}
+
+/// Pressure in display units, rounded like the formatted value.
+fn pressure_value(pa: f64, units: &Units) -> f64 {
+    round_to(convert(pa, Unit::Pascal, units.pressure.unit()), units.pressure.decimals())
+}
+
+fn format_pressure(pa: f64, units: &Units) -> String {
+    let value = convert(pa, Unit::Pascal, units.pressure.unit());
+    format!("{:.*} {}", units.pressure.decimals(), value, units.pressure.symbol())
+}
+
+fn format_visibility(meters: f64, units: &Units) -> String {
+    let to = units.distance.unit();
+    format!("{:.1} {}", convert(meters, Unit::Meter, to), to.symbol())
+}
+
+/// Precipitation in display units, rounded like the formatted value.
+fn precipitation_value(mm: f64, units: &Units) -> f64 {
+    round_to(convert(mm, Unit::Millimeter, units.precipitation.unit()), units.precipitation.decimals())
+}
+
+fn format_precipitation(mm: f64, units: &Units) -> String {
+    let to = units.precipitation.unit();
+    format!("{:.*} {}", units.precipitation.decimals(), convert(mm, Unit::Millimeter, to), to.symbol())
+}
+
+/// e.g. "Next 24h: 3.50 in snow, 0.62 in precipitation", leaving out
+/// amounts that round to nothing. `None` if nothing is expected at all.
+fn format_accumulation(accumulation: &Accumulation, units: &Units) -> Option<String> {
+    let to = units.precipitation.unit();
+    let amounts = [
+        ("snow", accumulation.snow),
+        ("ice", accumulation.ice),
+        ("precipitation", accumulation.precipitation),
+    ];
+    let amounts: Vec<String> = amounts.iter()
+        .filter(|(_, mm)| mm.is_some_and(|mm| round_to(convert(mm, Unit::Millimeter, to), units.precipitation.decimals()) > 0.0))
+        .filter_map(|(kind, mm)| mm.map(|mm| format!("{} {}", format_precipitation(mm, units), kind)))
+        .collect();
+    if amounts.is_empty() {
+        return None;
+    }
+    Some(format!("Next {}h: {}", accumulation.hours, amounts.join(", ")))
+}
+
+/// `weather-snow-heavy` and friends, for 24-hour totals above the thresholds.
+fn accumulation_classes(weather: &WeatherData, args: &Args) -> Vec<String> {
+    let Some(day) = weather.accumulations.iter().find(|accumulation| accumulation.hours == 24) else {
+        return Vec::new();
+    };
+    [
+        ("weather-snow-heavy", day.snow, args.heavy_snow),
+        ("weather-ice-heavy", day.ice, args.heavy_ice),
+        ("weather-rain-heavy", day.precipitation, args.heavy_rain),
+    ]
+    .into_iter()
+    .filter(|(_, mm, threshold)| mm.is_some_and(|mm| mm >= *threshold))
+    .map(|(class, _, _)| class.to_string())
+    .collect()
+}
+
+/// e.g. "observed 25 min ago at KXYZ"; `None` when the forecast was used.
+fn format_observation(weather: &WeatherData) -> Option<String> {
+    let age = weather.observation_age()?;
+    let station = weather.station.as_ref()?;
+    Some(format!("observed {} at {}", format_age(age), station.id))
+}
+
+fn format_station(station: &Station, units: &Units) -> String {
+    let mut text = station.id.clone();
+    if !station.name.is_empty() {
+        text.push_str(&format!(" ({})", station.name));
+    }
+    if let Some(km) = station.distance_km {
+        let to = units.distance.unit();
+        text.push_str(&format!(", {:.1} {} away", convert(km, Unit::Kilometer, to), to.symbol()));
+    }
+    text
+}
+
+fn format_cloud_layers(layers: &[CloudLayer], units: &Units) -> String {
+    let to = units.distance.short_unit();
+    layers.iter()
+        .map(|layer| match layer.base {
+            Some(base) => format!("{} {:.0} {}", layer.amount, convert(base, Unit::Meter, to), to.symbol()),
+            None => layer.amount.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Rounds for JSON output so converted values don't carry float noise.
+fn round_to(value: f64, decimals: usize) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+fn format_clock(time: Option<DateTime<Utc>>) -> Option<String> {
+    time.map(|time| time.with_timezone(&Local).format("%H:%M").to_string())
+}
+
+fn format_hours_minutes(duration: chrono::Duration) -> String {
+    format!("{}h {}m", duration.num_hours(), duration.num_minutes() % 60)
+}
+
+fn format_sun_times(astro: &AstroData) -> String {
+    match (format_clock(astro.sunrise), format_clock(astro.sunset)) {
+        (Some(sunrise), Some(sunset)) => format!(
+            "Sunrise: {}  Sunset: {} ({})",
+            sunrise,
+            sunset,
+            format_hours_minutes(astro.day_length())
+        ),
+        _ if astro.day_length_seconds > 0 => "Sun: up all day".to_string(),
+        _ => "Sun: down all day".to_string(),
+    }
+}
+
+pub fn template_values(location: &Location, weather: &WeatherData, args: &Args) -> TemplateValues {
+    let units = args.units();
+    let (temp, unit) = format_temperature(weather.temperature, &units.temperature, args.precision);
+    let astro = astro::compute(location, Local::now().date_naive());
+    let mut values = TemplateValues::new();
+
+    values.insert("icon", Some(get_condition_icon(weather.weather_condition(), astro::is_daylight(location, Utc::now()), &args.icons).to_string()));
+    values.insert("temp", Some(temp.to_string()));
+    values.insert("unit", Some(unit.to_string()));
+    values.insert("condition", Some(weather.condition.clone()));
+    values.insert("location", Some(location.name.clone()));
+    values.insert("updated", Some(weather.updated.with_timezone(&Local).format("%H:%M").to_string()));
+    values.insert("humidity", weather.humidity.map(|h| format!("{:.0}", h)));
+    values.insert("wind_speed", weather.wind_speed.map(|w| format_wind_value(w, &units.wind)));
+    values.insert("wind_gust", weather.wind_gust.map(|w| format_wind_value(w, &units.wind)));
+    values.insert("wind_unit", weather.wind_speed.map(|_| units.wind.symbol().to_string()));
+    values.insert("wind_dir", weather.wind_direction.map(|d| format!("{:.0}", d)));
+    values.insert("wind_dir_compass", weather.wind_direction.map(|d| wind::compass_direction(d).to_string()));
+    values.insert("wind_dir_compass8", weather.wind_direction.map(|d| wind::compass_direction_8(d).to_string()));
+    values.insert("wind_arrow", weather.wind_direction.map(|d| get_wind_arrow(d, &args.icons).to_string()));
+    values.insert("beaufort", weather.wind_speed.map(|w| beaufort_number(w).to_string()));
+    values.insert("beaufort_description", weather.wind_speed.map(|w| wind::beaufort_description(beaufort_number(w)).to_string()));
+    values.insert("high", weather.high.map(|t| format_temperature(t, &units.temperature, args.precision).0));
+    values.insert("low", weather.low.map(|t| format_temperature(t, &units.temperature, args.precision).0));
+    values.insert("pop", weather.precipitation_chance.map(|pop| format!("{:.0}", pop)));
+    values.insert("feels_like", weather.feels_like.map(|t| format_temperature(t, &units.temperature, args.precision).0));
+    values.insert("dewpoint", weather.dewpoint.map(|t| format_temperature(t, &units.temperature, args.precision).0));
+    values.insert("pressure", weather.sea_level_pressure.or(weather.pressure).map(|pa| {
+        format!("{:.*}", units.pressure.decimals(), convert(pa, Unit::Pascal, units.pressure.unit()))
+    }));
+    values.insert("pressure_unit", Some(units.pressure.symbol().to_string()));
+    values.insert("trend", weather.temperature_trend.map(|trend| get_trend_icon(trend, &args.icons).to_string()));
+    values.insert("pressure_trend", weather.pressure_trend.map(|trend| get_trend_icon(trend, &args.icons).to_string()));
+    values.insert("visibility", weather.visibility.map(|m| format!("{:.1}", convert(m, Unit::Meter, units.distance.unit()))));
+    values.insert("visibility_unit", Some(units.distance.unit().symbol().to_string()));
+    values.insert("station", weather.station.as_ref().map(|station| station.id.clone()));
+    values.insert("observed", weather.observation_age().map(format_age));
+    values.insert("alert", weather.alerts.first().map(|alert| alert.event.clone()));
+    values.insert("sunrise", format_clock(astro.sunrise));
+    values.insert("sunset", format_clock(astro.sunset));
+    values.insert("dawn", format_clock(astro.civil_twilight.rise));
+    values.insert("dusk", format_clock(astro.civil_twilight.set));
+    values.insert("day_length", Some(format_hours_minutes(astro.day_length())));
+    values.insert("moon_phase", Some(astro.moon_phase.name().to_string()));
+    values.insert("moon_illumination", Some(format!("{:.0}", astro.moon_illumination * 100.0)));
+
+    values
+}
+
+/// The `--format json` object for current conditions. Values are in the
+/// display units listed under `units`.
+#[derive(Serialize)]
+struct CurrentConditionsJson<'a> {
+    location: &'a str,
+    temperature: serde_json::Value,
+    unit: &'static str,
+    condition: &'a str,
+    condition_code: WeatherCondition,
+    icon: &'static str,
+    high: Option<serde_json::Value>,
+    low: Option<serde_json::Value>,
+    precipitation_chance: Option<f64>,
+    feels_like: Option<serde_json::Value>,
+    dewpoint: Option<serde_json::Value>,
+    heat_index: Option<serde_json::Value>,
+    wind_chill: Option<serde_json::Value>,
+    humidity: Option<f64>,
+    wind_speed: Option<f64>,
+    wind_direction: Option<f64>,
+    wind_direction_compass: Option<&'static str>,
+    wind_arrow: Option<&'static str>,
+    beaufort: Option<u8>,
+    beaufort_description: Option<&'static str>,
+    wind_gust: Option<f64>,
+    pressure: Option<f64>,
+    sea_level_pressure: Option<f64>,
+    visibility: Option<f64>,
+    cloud_layers: Vec<CloudLayerJson<'a>>,
+    precipitation_last_hour: Option<f64>,
+    precipitation_last_3_hours: Option<f64>,
+    precipitation_last_6_hours: Option<f64>,
+    accumulations: Vec<AccumulationJson>,
+    max_temperature_24h: Option<serde_json::Value>,
+    min_temperature_24h: Option<serde_json::Value>,
+    description: Option<&'a str>,
+    station: Option<StationJson<'a>>,
+    observed_at: Option<DateTime<Utc>>,
+    observation_age_seconds: Option<i64>,
+    observation: Option<String>,
+    temperature_trend: Option<Trend>,
+    pressure_trend: Option<Trend>,
+    temperature_source: &'static str,
+    alerts: &'a [Alert],
+    updated: DateTime<Utc>,
+    stale: bool,
+    units: UnitsJson,
+    astro: &'a AstroData,
+}
+
+#[derive(Serialize)]
+struct CloudLayerJson<'a> {
+    amount: &'a str,
+    base: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct AccumulationJson {
+    hours: u32,
+    snow: Option<f64>,
+    ice: Option<f64>,
+    precipitation: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct StationJson<'a> {
+    id: &'a str,
+    name: &'a str,
+    distance: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct UnitsJson {
+    temperature: &'static str,
+    wind: &'static str,
+    pressure: &'static str,
+    distance: &'static str,
+    height: &'static str,
+    precipitation: &'static str,
+}
+
+fn render_output(location: &Location, weather: &WeatherData, args: &Args, stale: bool) -> Result<String, WeatherError> {
+    let age = format_age(weather.age());
+    let astro = astro::compute(location, Local::now().date_naive());
+    let icon = get_condition_icon(weather.weather_condition(), astro::is_daylight(location, Utc::now()), &args.icons);
+    let units = args.units();
+    let (temp, unit) = format_temperature(weather.temperature, &units.temperature, args.precision);
+    let to_unit = |temp_c: Option<f64>| temp_c.map(|t| format_temperature(t, &units.temperature, args.precision).0);
+    let to_json = |temp_c: Option<f64>| json_temperature(temp_c, &units, args.precision);
+    let wind = |kmh: Option<f64>| kmh.map(|w| round_to(wind_value(w, &units.wind), 1));
+    let pressure = |pa: Option<f64>| pa.map(|pa| pressure_value(pa, &units));
+    let precipitation = |mm: Option<f64>| mm.map(|mm| precipitation_value(mm, &units));
+    let alert_prefix = if weather.alerts.is_empty() {
+        String::new()
+    } else {
+        format!("{} ", get_alert_icon(&args.icons))
+    };
+    let trend_suffix = match weather.temperature_trend {
+        Some(trend) if args.trend => format!(" {}", get_trend_icon(trend, &args.icons)),
+        _ => String::new(),
+    };
+    
+    match args.format {
+        OutputFormat::Plain => {
+            let mut output = match &args.text_template {
+                Some(template) => format!("{}{}", alert_prefix, template::render(template, &template_values(location, weather, args))?),
+                None => format!("{}{} {}{}{}  {}", alert_prefix, icon, temp, unit, trend_suffix, weather.condition),
+            };
+            if stale {
+                output.push_str(&format!(" (updated {})", age));
+            }
+            Ok(output)
+        }
+        OutputFormat::Json => {
+            let output = CurrentConditionsJson {
+                location: &location.name,
+                temperature: json_number(temperature_value(weather.temperature, &units.temperature, args.precision), args.precision),
+                unit,
+                condition: &weather.condition,
+                condition_code: weather.weather_condition(),
+                icon,
+                high: to_json(weather.high),
+                low: to_json(weather.low),
+                precipitation_chance: weather.precipitation_chance,
+                feels_like: to_json(weather.feels_like),
+                dewpoint: to_json(weather.dewpoint),
+                heat_index: to_json(weather.heat_index),
+                wind_chill: to_json(weather.wind_chill),
+                humidity: weather.humidity,
+                wind_speed: wind(weather.wind_speed),
+                wind_direction: weather.wind_direction,
+                wind_direction_compass: weather.wind_direction.map(wind::compass_direction),
+                wind_arrow: weather.wind_direction.map(|d| get_wind_arrow(d, &args.icons)),
+                beaufort: weather.wind_speed.map(beaufort_number),
+                beaufort_description: weather.wind_speed.map(|w| wind::beaufort_description(beaufort_number(w))),
+                wind_gust: wind(weather.wind_gust),
+                pressure: pressure(weather.pressure),
+                sea_level_pressure: pressure(weather.sea_level_pressure),
+                visibility: weather.visibility.map(|m| round_to(convert(m, Unit::Meter, units.distance.unit()), 1)),
+                cloud_layers: weather.cloud_layers.iter().map(|layer| CloudLayerJson {
+                    amount: &layer.amount,
+                    base: layer.base.map(|m| convert(m, Unit::Meter, units.distance.short_unit()).round()),
+                }).collect(),
+                precipitation_last_hour: precipitation(weather.precipitation_last_hour),
+                precipitation_last_3_hours: precipitation(weather.precipitation_last_3_hours),
+                precipitation_last_6_hours: precipitation(weather.precipitation_last_6_hours),
+                accumulations: weather.accumulations.iter().map(|accumulation| AccumulationJson {
+                    hours: accumulation.hours,
+                    snow: precipitation(accumulation.snow),
+                    ice: precipitation(accumulation.ice),
+                    precipitation: precipitation(accumulation.precipitation),
+                }).collect(),
+                max_temperature_24h: to_json(weather.max_temperature_24h),
+                min_temperature_24h: to_json(weather.min_temperature_24h),
+                description: weather.description.as_deref(),
+                station: weather.station.as_ref().map(|station| StationJson {
+                    id: &station.id,
+                    name: &station.name,
+                    distance: station.distance_km.map(|km| round_to(convert(km, Unit::Kilometer, units.distance.unit()), 1)),
+                }),
+                observed_at: weather.observed_at,
+                observation_age_seconds: weather.observation_age().map(|age| age.num_seconds()),
+                observation: format_observation(weather),
+                temperature_trend: weather.temperature_trend,
+                pressure_trend: weather.pressure_trend,
+                temperature_source: if weather.station.is_some() { "observation" } else { "forecast" },
+                alerts: &weather.alerts,
+                updated: weather.updated,
+                stale,
+                units: UnitsJson {
+                    temperature: unit,
+                    wind: units.wind.symbol(),
+                    pressure: units.pressure.symbol(),
+                    distance: units.distance.unit().symbol(),
+                    height: units.distance.short_unit().symbol(),
+                    precipitation: units.precipitation.unit().symbol(),
+                },
+                astro: &astro,
+            };
+            Ok(serde_json::to_string_pretty(&output)?)
+        }
+        OutputFormat::Waybar => {
+            let values = template_values(location, weather, args);
+            let text = match &args.text_template {
+                Some(template) => format!("{}{}", alert_prefix, template::render(template, &values)?),
+                None => format!("{}{} {}{}{}", alert_prefix, icon, temp, unit, trend_suffix),
+            };
             
-            let tooltip = if args.detailed {
+            let mut tooltip = if let Some(template) = &args.tooltip_template {
+                template::render(template, &values)?
+            } else if args.detailed {
                 let mut tooltip_parts = vec![
                     format!("{}: {}", location.name, weather.condition),
-                    format!("Temperature: {}{}", temp, unit),
+                    format!("Temperature: {}{}{}", temp, unit, format_trend(weather.temperature_trend, args)),
                 ];
+
+                match (to_unit(weather.high), to_unit(weather.low)) {
+                    (Some(high), Some(low)) => tooltip_parts.push(format!("High: {}{}  Low: {}{}", high, unit, low, unit)),
+                    (Some(high), None) => tooltip_parts.push(format!("High: {}{}", high, unit)),
+                    (None, Some(low)) => tooltip_parts.push(format!("Low: {}{}", low, unit)),
+                    (None, None) => {}
+                }
+
+                if let Some(pop) = weather.precipitation_chance {
+                    tooltip_parts.push(format!("Chance of precipitation: {:.0}%", pop));
+                }
+
+                if let Some(feels_like) = to_unit(weather.feels_like) {
+                    tooltip_parts.push(format!("Feels like: {}{}", feels_like, unit));
+                }
+
+                if let Some(dewpoint) = to_unit(weather.dewpoint) {
+                    tooltip_parts.push(format!("Dew point: {}{}", dewpoint, unit));
+                }
                 
                 if let Some(humidity) = weather.humidity {
                     tooltip_parts.push(format!("Humidity: {:.0}%", humidity));
                 }
                 
                 if let Some(wind_speed) = weather.wind_speed {
+                    let speed = format_wind_speed(wind_speed, &units.wind);
+                    let description = wind::beaufort_description(beaufort_number(wind_speed));
                     let wind_text = if let Some(wind_dir) = weather.wind_direction {
-                        format!("Wind: {:.0} mph from {}°", wind_speed * 2.237, wind_dir) // Convert m/s to mph
+                        format!(
+                            "Wind: {} {} from {} ({:.0}°), {}",
+                            get_wind_arrow(wind_dir, &args.icons),
+                            speed,
+                            wind::compass_direction(wind_dir),
+                            wind_dir,
+                            description.to_lowercase()
+                        )
                     } else {
-                        format!("Wind: {:.0} mph", wind_speed * 2.237)
+                        format!("Wind: {}, {}", speed, description.to_lowercase())
                     };
                     tooltip_parts.push(wind_text);
                 }
+
+                if let Some(gust) = weather.wind_gust {
+                    tooltip_parts.push(format!("Gusts: {}", format_wind_speed(gust, &units.wind)));
+                }
+
+                if let Some(pressure) = weather.sea_level_pressure.or(weather.pressure) {
+                    tooltip_parts.push(format!("Pressure: {}{}", format_pressure(pressure, &units), format_trend(weather.pressure_trend, args)));
+                }
+
+                if let Some(visibility) = weather.visibility {
+                    tooltip_parts.push(format!("Visibility: {}", format_visibility(visibility, &units)));
+                }
+
+                if !weather.cloud_layers.is_empty() {
+                    tooltip_parts.push(format!("Clouds: {}", format_cloud_layers(&weather.cloud_layers, &units)));
+                }
+
+                let precipitation = [
+                    ("1h", weather.precipitation_last_hour),
+                    ("3h", weather.precipitation_last_3_hours),
+                    ("6h", weather.precipitation_last_6_hours),
+                ];
+                let precipitation: Vec<String> = precipitation.iter()
+                    .filter_map(|(period, mm)| mm.map(|mm| format!("{} {}", format_precipitation(mm, &units), period)))
+                    .collect();
+                if !precipitation.is_empty() {
+                    tooltip_parts.push(format!("Precipitation: {}", precipitation.join(", ")));
+                }
+
+                tooltip_parts.extend(weather.accumulations.iter().filter_map(|accumulation| format_accumulation(accumulation, &units)));
+
+                if let (Some(high), Some(low)) = (to_unit(weather.max_temperature_24h), to_unit(weather.min_temperature_24h)) {
+                    tooltip_parts.push(format!("24h range: {}–{}{}", low, high, unit));
+                }
+
+                if let Some(description) = &weather.description {
+                    tooltip_parts.push(format!("Observed: {}", description));
+                }
+
+                match (&weather.station, weather.observation_age()) {
+                    (Some(station), Some(age)) => {
+                        tooltip_parts.push(format!("Observed {} at {}", format_age(age), format_station(station, &units)));
+                    }
+                    _ => tooltip_parts.push("No recent observation; showing forecast".to_string()),
+                }
+
+                tooltip_parts.push(format_sun_times(&astro));
+                tooltip_parts.push(format!(
+                    "Moon: {} ({:.0}%)",
+                    astro.moon_phase.name(),
+                    astro.moon_illumination * 100.0
+                ));
                 
                 tooltip_parts.join("\n")
             } else {
-                format!("{}: {}", location.name, weather.condition)
+                // Accumulations explain any weather-*-heavy class on hover
+                let mut tooltip_parts = vec![format!("{}: {}", location.name, weather.condition)];
+                tooltip_parts.extend(weather.accumulations.iter().filter_map(|accumulation| format_accumulation(accumulation, &units)));
+                tooltip_parts.join("\n")
             };
+
+            for alert in &weather.alerts {
+                tooltip.push_str(&format!("\n{} {}", get_alert_icon(&args.icons), format_alert_summary(alert)));
+            }
+
+            if stale {
+                tooltip.push_str(&format!("\nUpdated {}", age));
+            }
+
+            let mut class = vec![match weather.highest_alert_severity() {
+                Some(severity) => alert_class(severity),
+                None => "weather".to_string(),
+            }];
+            class.extend(accumulation_classes(weather, args));
+            if stale {
+                class.push("weather-stale".to_string());
+            }
             
             let output = WaybarOutput {
                 text,
                 tooltip,
-                class: "weather".to_string(),
+                class,
             };
             
             Ok(serde_json::to_string(&output)?)
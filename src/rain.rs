@@ -0,0 +1,150 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::Serialize;
+
+use crate::error::WeatherError;
+use crate::gridpoint::GridLayer;
+use crate::models::ForecastPeriod;
+use crate::units::Unit;
+
+// Hourly QPF at or above this is measurable precipitation, i.e. 0.01 in
+const MEASURABLE_PRECIPITATION_MM: f64 = 0.254;
+
+/// One forecast hour; `amount` is liquid precipitation in mm from the
+/// gridpoint QPF, if it was available.
+#[derive(Serialize, Clone, Debug)]
+pub struct RainHour {
+    pub start: DateTime<FixedOffset>,
+    pub end: DateTime<FixedOffset>,
+    pub chance: Option<f64>,
+    pub amount: Option<f64>,
+    pub wet: bool,
+}
+
+/// Whether precipitation is expected over the next few forecast hours.
+/// An hour counts as wet when its chance reaches the threshold or the QPF
+/// for it is measurable.
+#[derive(Serialize, Clone, Debug)]
+pub struct RainCheck {
+    pub hours: Vec<RainHour>,
+    /// Start of the first wet hour
+    pub start: Option<DateTime<FixedOffset>>,
+    /// End of that wet spell; `None` if it lasts past the last hour checked
+    pub stop: Option<DateTime<FixedOffset>>,
+}
+
+impl RainCheck {
+    pub fn new(periods: &[ForecastPeriod], qpf: Option<&GridLayer>, threshold: f64) -> Result<Self, WeatherError> {
+        let hours = periods.iter()
+            .map(|period| {
+                let (start, end) = (period.start()?, period.end()?);
+                let amount = match qpf {
+                    Some(layer) => {
+                        layer.total_between(start.with_timezone(&Utc), end.with_timezone(&Utc), Unit::Millimeter)?
+                    }
+                    None => None,
+                };
+                let chance = period.precipitation_chance();
+                let wet = chance.is_some_and(|chance| chance >= threshold)
+                    || amount.is_some_and(|mm| mm >= MEASURABLE_PRECIPITATION_MM);
+                Ok(RainHour { start, end, chance, amount, wet })
+            })
+            .collect::<Result<Vec<_>, WeatherError>>()?;
+
+        let first_wet = hours.iter().position(|hour| hour.wet);
+        let start = first_wet.map(|index| hours[index].start);
+        let stop = first_wet.and_then(|index| hours[index..].iter().find(|hour| !hour.wet)).map(|hour| hour.start);
+
+        Ok(Self { hours, start, stop })
+    }
+
+    pub fn expected(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn max_chance(&self) -> Option<f64> {
+        self.hours.iter().filter_map(|hour| hour.chance).reduce(f64::max)
+    }
+
+    /// End of the last hour checked.
+    pub fn end(&self) -> Option<DateTime<FixedOffset>> {
+        self.hours.last().map(|hour| hour.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hourly periods from 12:00 UTC, one per chance of precipitation.
+    fn hours(chances: &[Option<f64>]) -> Vec<ForecastPeriod> {
+        chances.iter()
+            .enumerate()
+            .map(|(hour, chance)| {
+                serde_json::from_value(serde_json::json!({
+                    "startTime": format!("2026-10-16T{:02}:00:00+00:00", 12 + hour),
+                    "endTime": format!("2026-10-16T{:02}:00:00+00:00", 13 + hour),
+                    "isDaytime": true,
+                    "temperature": 60,
+                    "temperatureUnit": "F",
+                    "windSpeed": null,
+                    "windDirection": null,
+                    "icon": null,
+                    "shortForecast": "",
+                    "probabilityOfPrecipitation": { "unitCode": "wmoUnit:percent", "value": chance }
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn qpf(values: &[(&str, f64)]) -> GridLayer {
+        let values: Vec<_> = values.iter()
+            .map(|(valid_time, value)| serde_json::json!({ "validTime": valid_time, "value": value }))
+            .collect();
+        serde_json::from_value(serde_json::json!({ "uom": "wmoUnit:mm", "values": values })).unwrap()
+    }
+
+    fn hour(hour: u32) -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339(&format!("2026-10-16T{:02}:00:00+00:00", hour)).unwrap()
+    }
+
+    #[test]
+    fn dry_hours() {
+        let check = RainCheck::new(&hours(&[Some(10.0), None, Some(20.0)]), None, 50.0).unwrap();
+        assert!(!check.expected());
+        assert_eq!(check.start, None);
+        assert_eq!(check.stop, None);
+        assert_eq!(check.max_chance(), Some(20.0));
+        assert_eq!(check.end(), Some(hour(15)));
+    }
+
+    #[test]
+    fn wet_from_the_first_hour() {
+        let check = RainCheck::new(&hours(&[Some(80.0), Some(70.0), Some(60.0)]), None, 50.0).unwrap();
+        assert!(check.expected());
+        assert_eq!(check.start, Some(hour(12)));
+        assert_eq!(check.stop, None);
+    }
+
+    #[test]
+    fn spell_that_stops() {
+        let check = RainCheck::new(&hours(&[Some(20.0), Some(50.0), Some(60.0), Some(30.0), Some(70.0)]), None, 50.0).unwrap();
+        assert_eq!(check.start, Some(hour(13)));
+        assert_eq!(check.stop, Some(hour(15)));
+        // The chance threshold is inclusive
+        assert!(check.hours[1].wet);
+        assert!(!check.hours[3].wet);
+    }
+
+    #[test]
+    fn measurable_qpf_is_wet_without_a_high_chance() {
+        // 1.2 mm over the 3h from 13:00 is 0.4 mm/h; 0.6 mm over 3h from 16:00 is 0.2 mm/h
+        let layer = qpf(&[("2026-10-16T13:00:00+00:00/PT3H", 1.2), ("2026-10-16T16:00:00+00:00/PT3H", 0.6)]);
+        let check = RainCheck::new(&hours(&[Some(10.0), Some(10.0), Some(10.0), Some(10.0), Some(10.0)]), Some(&layer), 50.0).unwrap();
+        assert_eq!(check.hours[0].amount, None);
+        assert!((check.hours[1].amount.unwrap() - 0.4).abs() < 1e-9);
+        assert!((check.hours[4].amount.unwrap() - 0.2).abs() < 1e-9);
+        assert_eq!(check.start, Some(hour(13)));
+        assert_eq!(check.stop, Some(hour(16)));
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::error::WeatherError;
+
+/// Placeholder values for a template; `None` marks data that isn't available.
+pub type TemplateValues = HashMap<&'static str, Option<String>>;
+
+enum Node {
+    Text(String),
+    Field(String),
+    /// `[...]`: dropped entirely if any placeholder inside it has no value
+    Section(Vec<Node>),
+}
+
+/// Renders a user template such as `{icon} {temp}{unit}[ {humidity}%]`.
+///
+/// `{name}` is replaced by the named value, `[...]` marks an optional section
+/// and `\n`, `\{`, `\}`, `\[`, `\]` and `\\` are escapes. Missing values outside
+/// a section render as an empty string; unknown placeholders are an error.
+pub fn render(template: &str, values: &TemplateValues) -> Result<String, WeatherError> {
+    let nodes = parse(&mut template.chars().peekable(), false)
+        .map_err(|e| WeatherError::Template(format!("{} in \"{}\"", e, template)))?;
+    let mut output = String::new();
+    render_nodes(&nodes, values, &mut output)?;
+    Ok(output)
+}
+
+fn parse(chars: &mut Peekable<Chars>, in_section: bool) -> Result<Vec<Node>, String> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => text.push('\n'),
+                Some('t') => text.push('\t'),
+                Some(escaped @ ('{' | '}' | '[' | ']' | '\\')) => text.push(escaped),
+                Some(other) => {
+                    text.push('\\');
+                    text.push(other);
+                }
+                None => text.push('\\'),
+            },
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err("unclosed '{'".to_string()),
+                    }
+                }
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(Node::Field(name.trim().to_string()));
+            }
+            '[' => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(Node::Section(parse(chars, true)?));
+            }
+            ']' if in_section => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(text));
+                }
+                return Ok(nodes);
+            }
+            '}' | ']' => return Err(format!("unexpected '{}'", c)),
+            c => text.push(c),
+        }
+    }
+
+    if in_section {
+        return Err("unclosed '['".to_string());
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok(nodes)
+}
+
+/// Appends the rendered nodes to `output`, returning `false` if any value was
+/// missing so an enclosing section can be dropped.
+fn render_nodes(nodes: &[Node], values: &TemplateValues, output: &mut String) -> Result<bool, WeatherError> {
+    // Keep going after a missing value so unknown placeholders are always reported
+    let mut complete = true;
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Field(name) => match values.get(name.as_str()) {
+                Some(Some(value)) => output.push_str(value),
+                Some(None) => complete = false,
+                None => return Err(WeatherError::Template(format!("unknown placeholder {{{}}}", name))),
+            },
+            Node::Section(children) => {
+                let mut section = String::new();
+                if render_nodes(children, values, &mut section)? {
+                    output.push_str(&section);
+                }
+            }
+        }
+    }
+    Ok(complete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> TemplateValues {
+        let mut values = TemplateValues::new();
+        values.insert("temp", Some("72".to_string()));
+        values.insert("unit", Some("°F".to_string()));
+        values.insert("humidity", None);
+        values.insert("wind_speed", Some("5".to_string()));
+        values
+    }
+
+    #[test]
+    fn replaces_placeholders_and_escapes() {
+        assert_eq!(render("{temp}{unit}", &values()).unwrap(), "72°F");
+        assert_eq!(render("{ temp }", &values()).unwrap(), "72");
+        assert_eq!(render(r"a\nb \{temp\} \[x\] \\", &values()).unwrap(), "a\nb {temp} [x] \\");
+        // Missing values outside a section just render empty
+        assert_eq!(render("{temp} {humidity}%", &values()).unwrap(), "72 %");
+    }
+
+    #[test]
+    fn drops_sections_with_missing_values() {
+        assert_eq!(render("{temp}[ {humidity}%]", &values()).unwrap(), "72");
+        assert_eq!(render("{temp}[ {wind_speed} mph]", &values()).unwrap(), "72 5 mph");
+    }
+
+    #[test]
+    fn nested_sections_drop_independently() {
+        // Only the inner section is dropped; the outer one is still complete
+        assert_eq!(render("[{temp}[ {humidity}%] {wind_speed}]", &values()).unwrap(), "72 5");
+        assert_eq!(render("[{humidity}[ {temp}]]", &values()).unwrap(), "");
+    }
+
+    #[test]
+    fn unknown_placeholders_fail_even_in_dropped_sections() {
+        assert!(render("{nope}", &values()).is_err());
+        assert!(render("[{humidity} {nope}]", &values()).is_err());
+    }
+
+    #[test]
+    fn rejects_unbalanced_brackets() {
+        assert!(render("{temp", &values()).is_err());
+        assert!(render("[{temp}", &values()).is_err());
+        assert!(render("{temp}]", &values()).is_err());
+        assert!(render("temp}", &values()).is_err());
+    }
+}
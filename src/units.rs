@@ -0,0 +1,223 @@
+use crate::error::WeatherError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+    KilometersPerHour,
+    MetersPerSecond,
+    MilesPerHour,
+    Knots,
+    Pascal,
+    Hectopascal,
+    InchesOfMercury,
+    MillimetersOfMercury,
+    Millimeter,
+    Centimeter,
+    Meter,
+    Kilometer,
+    Inch,
+    Foot,
+    Mile,
+    Percent,
+    Degree,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dimension {
+    Temperature,
+    Speed,
+    Pressure,
+    Length,
+    Ratio,
+    Angle,
+}
+
+impl Unit {
+    /// Parses NWS `unitCode` values such as `wmoUnit:km_h-1` or `wmoUnit:degC`.
+    /// Unrecognized codes are an error rather than a guess.
+    pub fn from_code(code: &str) -> Result<Self, WeatherError> {
+        let name = code.rsplit(':').next().unwrap_or(code);
+        let unit = match name {
+            "degC" => Unit::Celsius,
+            "degF" => Unit::Fahrenheit,
+            "K" => Unit::Kelvin,
+            "km_h-1" => Unit::KilometersPerHour,
+            "m_s-1" => Unit::MetersPerSecond,
+            "mi_h-1" => Unit::MilesPerHour,
+            "kt" | "knot" => Unit::Knots,
+            "Pa" => Unit::Pascal,
+            "hPa" => Unit::Hectopascal,
+            "mm" => Unit::Millimeter,
+            "cm" => Unit::Centimeter,
+            "m" => Unit::Meter,
+            "km" => Unit::Kilometer,
+            "in" => Unit::Inch,
+            "ft" => Unit::Foot,
+            "mi" => Unit::Mile,
+            "percent" => Unit::Percent,
+            "degree_(angle)" => Unit::Degree,
+            _ => return Err(WeatherError::UnknownUnit(code.to_string())),
+        };
+        Ok(unit)
+    }
+
+    pub fn dimension(self) -> Dimension {
+        match self {
+            Unit::Celsius | Unit::Fahrenheit | Unit::Kelvin => Dimension::Temperature,
+            Unit::KilometersPerHour | Unit::MetersPerSecond | Unit::MilesPerHour | Unit::Knots => Dimension::Speed,
+            Unit::Pascal | Unit::Hectopascal | Unit::InchesOfMercury | Unit::MillimetersOfMercury => Dimension::Pressure,
+            Unit::Millimeter | Unit::Centimeter | Unit::Meter | Unit::Kilometer | Unit::Inch | Unit::Foot | Unit::Mile => {
+                Dimension::Length
+            }
+            Unit::Percent => Dimension::Ratio,
+            Unit::Degree => Dimension::Angle,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Fahrenheit => "°F",
+            Unit::Kelvin => "K",
+            Unit::KilometersPerHour => "km/h",
+            Unit::MetersPerSecond => "m/s",
+            Unit::MilesPerHour => "mph",
+            Unit::Knots => "kt",
+            Unit::Pascal => "Pa",
+            Unit::Hectopascal => "hPa",
+            Unit::InchesOfMercury => "inHg",
+            Unit::MillimetersOfMercury => "mmHg",
+            Unit::Millimeter => "mm",
+            Unit::Centimeter => "cm",
+            Unit::Meter => "m",
+            Unit::Kilometer => "km",
+            Unit::Inch => "in",
+            Unit::Foot => "ft",
+            Unit::Mile => "mi",
+            Unit::Percent => "%",
+            Unit::Degree => "°",
+        }
+    }
+
+    // Conversions go through one base unit per dimension: °C, m/s, Pa, m
+    fn base_value(self, value: f64) -> f64 {
+        match self {
+            Unit::Fahrenheit => (value - 32.0) * 5.0 / 9.0,
+            Unit::Kelvin => value - 273.15,
+            Unit::KilometersPerHour => value / 3.6,
+            Unit::MilesPerHour => value * 0.44704,
+            Unit::Knots => value * 1852.0 / 3600.0,
+            Unit::Hectopascal => value * 100.0,
+            Unit::InchesOfMercury => value * 3386.389,
+            Unit::MillimetersOfMercury => value * 133.322387,
+            Unit::Millimeter => value / 1000.0,
+            Unit::Centimeter => value / 100.0,
+            Unit::Kilometer => value * 1000.0,
+            Unit::Inch => value * 0.0254,
+            Unit::Foot => value * 0.3048,
+            Unit::Mile => value * 1609.344,
+            _ => value,
+        }
+    }
+
+    fn value_from_base(self, value: f64) -> f64 {
+        match self {
+            Unit::Fahrenheit => value * 9.0 / 5.0 + 32.0,
+            Unit::Kelvin => value + 273.15,
+            Unit::KilometersPerHour => value * 3.6,
+            Unit::MilesPerHour => value / 0.44704,
+            Unit::Knots => value * 3600.0 / 1852.0,
+            Unit::Hectopascal => value / 100.0,
+            Unit::InchesOfMercury => value / 3386.389,
+            Unit::MillimetersOfMercury => value / 133.322387,
+            Unit::Millimeter => value * 1000.0,
+            Unit::Centimeter => value * 100.0,
+            Unit::Kilometer => value / 1000.0,
+            Unit::Inch => value / 0.0254,
+            Unit::Foot => value / 0.3048,
+            Unit::Mile => value / 1609.344,
+            _ => value,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self { value, unit }
+    }
+
+    pub fn to(self, unit: Unit) -> Result<f64, WeatherError> {
+        if self.unit.dimension() != unit.dimension() {
+            return Err(WeatherError::UnitMismatch(format!(
+                "cannot convert {} to {}",
+                self.unit.symbol(),
+                unit.symbol()
+            )));
+        }
+        Ok(convert(self.value, self.unit, unit))
+    }
+}
+
+/// Converts between two units of the same dimension, for callers that pick
+/// both units themselves rather than reading them from a response.
+pub(crate) fn convert(value: f64, from: Unit, to: Unit) -> f64 {
+    debug_assert_eq!(from.dimension(), to.dimension());
+    if from == to {
+        return value;
+    }
+    to.value_from_base(from.base_value(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn parses_wmo_unit_codes() {
+        assert_eq!(Unit::from_code("wmoUnit:km_h-1").unwrap(), Unit::KilometersPerHour);
+        assert_eq!(Unit::from_code("wmoUnit:m_s-1").unwrap(), Unit::MetersPerSecond);
+        assert_eq!(Unit::from_code("wmoUnit:degC").unwrap(), Unit::Celsius);
+        assert_eq!(Unit::from_code("wmoUnit:Pa").unwrap(), Unit::Pascal);
+        assert_eq!(Unit::from_code("wmoUnit:degree_(angle)").unwrap(), Unit::Degree);
+        assert_eq!(Unit::from_code("percent").unwrap(), Unit::Percent);
+        assert!(matches!(Unit::from_code("wmoUnit:furlong"), Err(WeatherError::UnknownUnit(_))));
+    }
+
+    #[test]
+    fn converts_wind_speeds() {
+        // km/h must not be treated as m/s
+        let mph = Quantity::new(16.09, Unit::from_code("wmoUnit:km_h-1").unwrap()).to(Unit::MilesPerHour).unwrap();
+        assert!(close(mph, 10.0, 0.01));
+        assert!(close(convert(10.0, Unit::MetersPerSecond, Unit::KilometersPerHour), 36.0, 1e-9));
+        assert!(close(convert(10.0, Unit::Knots, Unit::KilometersPerHour), 18.52, 1e-9));
+    }
+
+    #[test]
+    fn converts_temperatures_pressures_and_lengths() {
+        assert!(close(convert(100.0, Unit::Celsius, Unit::Fahrenheit), 212.0, 1e-9));
+        assert!(close(convert(-40.0, Unit::Fahrenheit, Unit::Celsius), -40.0, 1e-9));
+        assert!(close(convert(0.0, Unit::Kelvin, Unit::Celsius), -273.15, 1e-9));
+        assert!(close(convert(101_325.0, Unit::Pascal, Unit::InchesOfMercury), 29.92, 0.01));
+        assert!(close(convert(1013.25, Unit::Hectopascal, Unit::MillimetersOfMercury), 760.0, 0.01));
+        assert!(close(convert(25.4, Unit::Millimeter, Unit::Inch), 1.0, 1e-9));
+        assert!(close(convert(1609.344, Unit::Meter, Unit::Mile), 1.0, 1e-9));
+    }
+
+    #[test]
+    fn refuses_to_convert_across_dimensions() {
+        assert!(matches!(Quantity::new(20.0, Unit::Celsius).to(Unit::Pascal), Err(WeatherError::UnitMismatch(_))));
+        assert!(Quantity::new(5.0, Unit::MetersPerSecond).to(Unit::Meter).is_err());
+    }
+}
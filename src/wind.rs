@@ -0,0 +1,56 @@
+const COMPASS_16: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+    "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+];
+
+const COMPASS_8: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+// Upper bound of each Beaufort force, in km/h
+const BEAUFORT_LIMITS: [f64; 12] = [1.0, 6.0, 12.0, 20.0, 29.0, 39.0, 50.0, 62.0, 75.0, 89.0, 103.0, 118.0];
+
+const BEAUFORT_DESCRIPTIONS: [&str; 13] = [
+    "Calm",
+    "Light air",
+    "Light breeze",
+    "Gentle breeze",
+    "Moderate breeze",
+    "Fresh breeze",
+    "Strong breeze",
+    "Near gale",
+    "Gale",
+    "Strong gale",
+    "Storm",
+    "Violent storm",
+    "Hurricane force",
+];
+
+/// Index of the nearest of `points` evenly spaced directions, clockwise from north.
+fn sector(degrees: f64, points: usize) -> usize {
+    let width = 360.0 / points as f64;
+    (degrees.rem_euclid(360.0) / width).round() as usize % points
+}
+
+/// 16-point compass name for the direction the wind blows from, e.g. "WSW".
+pub fn compass_direction(degrees: f64) -> &'static str {
+    COMPASS_16[sector(degrees, COMPASS_16.len())]
+}
+
+/// 8-point compass name for the direction the wind blows from, e.g. "SW".
+pub fn compass_direction_8(degrees: f64) -> &'static str {
+    COMPASS_8[sector(degrees, COMPASS_8.len())]
+}
+
+/// 8-point index (0 = north) of the direction the wind blows toward, which is
+/// where a wind arrow points.
+pub fn arrow_sector(degrees: f64) -> usize {
+    sector(degrees + 180.0, 8)
+}
+
+/// Beaufort force (0-12) for a wind speed in km/h.
+pub fn beaufort_number(kmh: f64) -> u8 {
+    BEAUFORT_LIMITS.iter().take_while(|&&limit| kmh >= limit).count() as u8
+}
+
+pub fn beaufort_description(force: u8) -> &'static str {
+    BEAUFORT_DESCRIPTIONS[(force as usize).min(BEAUFORT_DESCRIPTIONS.len() - 1)]
+}